@@ -0,0 +1,175 @@
+use crate::run::{self, Params};
+use crate::verify;
+use anyhow::Context;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Wall-clock samples of one part on one input, with the parameters from its answers file.
+#[derive(Debug)]
+pub struct Timing {
+    pub day: u8,
+    pub part: u8,
+    pub input: String,
+    pub params: Params,
+    pub samples: Vec<Duration>,
+    /// Set when the answer did not match the answers file.
+    pub wrong: Option<String>,
+}
+
+impl Timing {
+    pub fn min(&self) -> Duration {
+        self.samples.iter().copied().min().unwrap_or_default()
+    }
+
+    pub fn median(&self) -> Duration {
+        let mut samples = self.samples.clone();
+        samples.sort();
+        samples.get(samples.len() / 2).copied().unwrap_or_default()
+    }
+
+    pub fn mean(&self) -> Duration {
+        let total: Duration = self.samples.iter().sum();
+        total / self.samples.len().max(1).try_into().unwrap()
+    }
+}
+
+pub fn format_duration(d: Duration) -> String {
+    format!("{:.3} ms", d.as_secs_f64() * 1000.0)
+}
+
+impl std::fmt::Display for Timing {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut name = format!("day{:02} part{} {}", self.day, self.part, self.input);
+        let params = self.params.to_string();
+        if !params.is_empty() {
+            name = format!("{name} {params}");
+        }
+        write!(
+            f,
+            "{name:<40} min {:>12}  median {:>12}  mean {:>12}",
+            format_duration(self.min()),
+            format_duration(self.median()),
+            format_duration(self.mean()),
+        )?;
+        if let Some(actual) = &self.wrong {
+            write!(f, "  WRONG ANSWER {actual}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Times every answer in the answers file at `path` over `iterations` runs, after one
+/// untimed warm-up run. `extra` params are appended to each answer's own, except those the
+/// part does not take. Parts the solver does not support are left out.
+pub fn bench_file(
+    day: u8,
+    path: &Path,
+    iterations: usize,
+    extra: &Params,
+) -> anyhow::Result<Vec<Timing>> {
+    let answers = std::fs::read_to_string(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|s| Ok(verify::parse_answers(&s)?))
+        .with_context(|| format!("reading {}", path.display()))?;
+    let input_path = path.with_extension("txt");
+    let input = std::fs::read_to_string(&input_path)
+        .with_context(|| format!("reading {}", input_path.display()))?;
+    let input_name = input_path.file_name().unwrap_or_default().to_string_lossy();
+    let mut timings = vec![];
+    for answer in answers {
+        let mut extra = extra.clone();
+        let outcome = loop {
+            let mut params = answer.params.clone();
+            params.extend(&extra);
+            match run::run(day, answer.part, &input, &params) {
+                Ok(actual) => break Some((params, actual)),
+                Err(err) => match err.downcast_ref::<common::Error>() {
+                    Some(common::Error::Unsupported(_)) => break None,
+                    Some(common::Error::UnknownParam(name)) if extra.contains(name) => {
+                        extra.remove(name)
+                    }
+                    _ => return Err(err.context(format!("day{day:02} part{}", answer.part))),
+                },
+            }
+        };
+        let Some((params, actual)) = outcome else {
+            continue;
+        };
+        let mut samples = Vec::with_capacity(iterations);
+        for _ in 0..iterations {
+            let start = Instant::now();
+            std::hint::black_box(run::run(day, answer.part, &input, &params)?);
+            samples.push(start.elapsed());
+        }
+        timings.push(Timing {
+            day,
+            part: answer.part,
+            input: input_name.to_string(),
+            params,
+            samples,
+            wrong: (actual != answer.expected).then_some(actual),
+        });
+    }
+    Ok(timings)
+}
+
+/// Answers files worth benchmarking: the full puzzle inputs, not the examples.
+pub fn bench_files(root: &Path, day: Option<u8>) -> anyhow::Result<Vec<(u8, std::path::PathBuf)>> {
+    Ok(verify::answer_files(root, day)?
+        .into_iter()
+        .filter(|(_, path)| {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            !stem.starts_with("example")
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    #[test]
+    fn timing_stats() {
+        let timing = super::Timing {
+            day: 1,
+            part: 1,
+            input: "big.txt".into(),
+            params: Default::default(),
+            samples: [5, 1, 3, 2, 4].map(Duration::from_millis).to_vec(),
+            wrong: None,
+        };
+        assert_eq!(timing.min(), Duration::from_millis(1));
+        assert_eq!(timing.median(), Duration::from_millis(3));
+        assert_eq!(timing.mean(), Duration::from_millis(3));
+    }
+
+    #[test]
+    fn bench_day01() -> anyhow::Result<()> {
+        let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+        let files = super::bench_files(&root, Some(1))?;
+        assert_eq!(files.len(), 1);
+        let timings = super::bench_file(1, &files[0].1, 2, &Default::default())?;
+        assert_eq!(timings.len(), 2);
+        assert!(timings
+            .iter()
+            .all(|t| t.samples.len() == 2 && t.wrong.is_none()));
+        Ok(())
+    }
+
+    #[test]
+    fn bench_extra_params() -> anyhow::Result<()> {
+        let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+        let path = root.join("day08/src/ghosts.answers");
+        let mut extra = crate::run::Params::default();
+        extra.insert("merge=slow")?;
+        let timings = super::bench_file(8, &path, 1, &extra)?;
+        let params = timings.iter().map(|t| (t.part, t.params.to_string()));
+        assert_eq!(params.collect::<Vec<_>>(), [(2, "merge=slow".to_string())]);
+
+        let files = super::bench_files(&root, Some(1))?;
+        let timings = super::bench_file(1, &files[0].1, 1, &extra)?;
+        assert_eq!(timings.len(), 2);
+        assert!(timings.iter().all(|t| t.params.to_string().is_empty()));
+        Ok(())
+    }
+}
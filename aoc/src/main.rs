@@ -0,0 +1,184 @@
+use anyhow::{anyhow, bail, Context};
+use std::io::Read;
+
+mod bench;
+mod run;
+mod verify;
+
+const USAGE: &str = "\
+usage: aoc run --day <N> --part <1|2> [--input <path>] [--param <name>=<value>]...
+       aoc verify [--day <N>] [--root <dir>]
+       aoc bench [--day <N>] [--root <dir>] [--iterations <N>] [--param <name>=<value>]...
+
+run reads the puzzle input from <path>, or from stdin if --input is absent or \"-\".
+
+verify checks every dayNN/src/<name>.answers under <dir> (default: the current
+directory) against the answers computed from dayNN/src/<name>.txt. Each line of
+an answers file reads `part<1|2> [<name>=<value>]... = <answer>`.
+
+bench times each part on every full input that has an answers file (skipping
+example*.answers), <N> runs after a warm-up (default 10), and reports per-day
+totals of the medians. --param is added to the runs of every part that takes it,
+e.g. to pick a variant:
+`aoc bench --day 21 --param compose=dense` or `aoc bench --day 8 --param merge=slow`.
+";
+
+#[derive(Debug, Default)]
+struct RunArgs {
+    day: Option<u8>,
+    part: Option<u8>,
+    input: Option<String>,
+    params: run::Params,
+}
+
+impl RunArgs {
+    fn parse(mut args: impl Iterator<Item = String>) -> anyhow::Result<Self> {
+        let mut result = Self::default();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(anyhow!("missing value for {arg}"));
+            match arg.as_str() {
+                "--day" => result.day = Some(value()?.parse().context("--day")?),
+                "--part" => result.part = Some(value()?.parse().context("--part")?),
+                "--input" => result.input = Some(value()?),
+                "--param" => result.params.insert(&value()?)?,
+                _ => bail!("unexpected argument: {arg}"),
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Default)]
+struct VerifyArgs {
+    day: Option<u8>,
+    root: Option<String>,
+}
+
+impl VerifyArgs {
+    fn parse(mut args: impl Iterator<Item = String>) -> anyhow::Result<Self> {
+        let mut result = Self::default();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(anyhow!("missing value for {arg}"));
+            match arg.as_str() {
+                "--day" => result.day = Some(value()?.parse().context("--day")?),
+                "--root" => result.root = Some(value()?),
+                _ => bail!("unexpected argument: {arg}"),
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Default)]
+struct BenchArgs {
+    day: Option<u8>,
+    root: Option<String>,
+    iterations: Option<usize>,
+    params: run::Params,
+}
+
+impl BenchArgs {
+    fn parse(mut args: impl Iterator<Item = String>) -> anyhow::Result<Self> {
+        let mut result = Self::default();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(anyhow!("missing value for {arg}"));
+            match arg.as_str() {
+                "--day" => result.day = Some(value()?.parse().context("--day")?),
+                "--root" => result.root = Some(value()?),
+                "--iterations" => {
+                    result.iterations = Some(value()?.parse().context("--iterations")?)
+                }
+                "--param" => result.params.insert(&value()?)?,
+                _ => bail!("unexpected argument: {arg}"),
+            }
+        }
+        Ok(result)
+    }
+}
+
+fn read_input(path: Option<&str>) -> anyhow::Result<String> {
+    match path {
+        None | Some("-") => {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        }
+        Some(path) => std::fs::read_to_string(path).with_context(|| format!("reading {path}")),
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        Some("run") => {
+            let args = RunArgs::parse(args)?;
+            let day = args.day.ok_or(anyhow!("--day is required"))?;
+            let part = args.part.ok_or(anyhow!("--part is required"))?;
+            let input = read_input(args.input.as_deref())?;
+            println!("{}", run::run(day, part, &input, &args.params)?);
+            Ok(())
+        }
+        Some("verify") => {
+            let args = VerifyArgs::parse(args)?;
+            let root = std::path::Path::new(args.root.as_deref().unwrap_or("."));
+            let files = verify::answer_files(root, args.day)?;
+            if files.is_empty() {
+                bail!("no answers files under {}", root.display());
+            }
+            let (mut ok, mut failed, mut skipped) = (0, 0, 0);
+            for (day, path) in files {
+                for check in verify::verify_file(day, &path)? {
+                    println!("{check}");
+                    match check.outcome {
+                        verify::Outcome::Ok => ok += 1,
+                        verify::Outcome::Skipped(_) => skipped += 1,
+                        _ => failed += 1,
+                    }
+                }
+            }
+            println!("{ok} ok, {failed} failed, {skipped} skipped");
+            if failed > 0 {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Some("bench") => {
+            let args = BenchArgs::parse(args)?;
+            let root = std::path::Path::new(args.root.as_deref().unwrap_or("."));
+            let iterations = args.iterations.unwrap_or(10).max(1);
+            let files = bench::bench_files(root, args.day)?;
+            if files.is_empty() {
+                bail!("no answers files under {}", root.display());
+            }
+            let mut totals = std::collections::BTreeMap::<u8, std::time::Duration>::new();
+            let mut unused = args.params.clone();
+            for (day, path) in files {
+                for timing in bench::bench_file(day, &path, iterations, &args.params)? {
+                    println!("{timing}");
+                    *totals.entry(day).or_default() += timing.median();
+                    for (name, _) in timing.params.as_pairs() {
+                        unused.remove(name);
+                    }
+                }
+            }
+            if let Some((name, _)) = unused.as_pairs().first() {
+                bail!("no part takes parameter {name}");
+            }
+            println!();
+            for (day, total) in &totals {
+                println!("day{day:02} {:>12}", bench::format_duration(*total));
+            }
+            let total = totals.values().sum();
+            println!("total {:>12}", bench::format_duration(total));
+            Ok(())
+        }
+        Some("-h" | "--help") => {
+            print!("{USAGE}");
+            Ok(())
+        }
+        _ => {
+            eprint!("{USAGE}");
+            std::process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use anyhow::{anyhow, bail};
+
+/// Extra `name=value` arguments for solvers that take more than their input.
+#[derive(Debug, Clone, Default)]
+pub struct Params {
+    values: Vec<(String, String)>,
+}
+
+impl Params {
+    pub fn insert(&mut self, s: &str) -> anyhow::Result<()> {
+        let (name, value) = s
+            .split_once('=')
+            .ok_or(anyhow!("expected name=value, got {s:?}"))?;
+        self.push(name.trim(), value.trim());
+        Ok(())
+    }
+
+    pub fn push(&mut self, name: &str, value: &str) {
+        self.values.push((name.into(), value.into()));
+    }
+
+    pub fn extend(&mut self, other: &Params) {
+        self.values.extend(other.values.iter().cloned());
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.values.iter().any(|(n, _)| n == name)
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.values.retain(|(n, _)| n != name);
+    }
+
+    pub fn as_pairs(&self) -> Vec<(&str, &str)> {
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect()
+    }
+}
+
+impl std::fmt::Display for Params {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (i, (name, value)) in self.values.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{name}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs part `part` of day `day` on `input` and returns the answer as text.
+pub fn run(day: u8, part: u8, input: &str, params: &Params) -> anyhow::Result<String> {
+    let params = params.as_pairs();
+    let run = match day {
+        1 => common::run::<day01::Day01>,
+        2 => common::run::<day02::Day02>,
+        3 => common::run::<day03::Day03>,
+        4 => common::run::<day04::Day04>,
+        5 => common::run::<day05::Day05>,
+        6 => common::run::<day06::Day06>,
+        7 => common::run::<day07::Day07>,
+        8 => common::run::<day08::Day08>,
+        9 => common::run::<day09::Day09>,
+        10 => common::run::<day10::Day10>,
+        11 => common::run::<day11::Day11>,
+        12 => common::run::<day12::Day12>,
+        13 => common::run::<day13::Day13>,
+        14 => common::run::<day14::Day14>,
+        15 => common::run::<day15::Day15>,
+        16 => common::run::<day16::Day16>,
+        17 => common::run::<day17::Day17>,
+        18 => common::run::<day18::Day18>,
+        19 => common::run::<day19::Day19>,
+        20 => common::run::<day20::Day20>,
+        21 => common::run::<day21::Day21>,
+        22 => common::run::<day22::Day22>,
+        23 => common::run::<day23::Day23>,
+        #[cfg(feature = "day24")]
+        24 => common::run::<day24::Day24>,
+        #[cfg(not(feature = "day24"))]
+        24 => {
+            return Err(
+                common::Error::Unsupported("day 24 was disabled at build time".into()).into(),
+            )
+        }
+        _ => bail!("no such day: {day}"),
+    };
+    Ok(run(part, input, &params)?)
+}
@@ -0,0 +1,183 @@
+use crate::run::{self, Params};
+use anyhow::Context;
+use std::path::{Path, PathBuf};
+
+/// One line of an answers file: `part<N> [name=value]... = <answer>`.
+#[derive(Debug, Clone)]
+pub struct Answer {
+    pub part: u8,
+    pub params: Params,
+    pub expected: String,
+}
+
+/// Parses the expected answers for one input. Blank lines and `#` comments are ignored.
+pub fn parse_answers(s: &str) -> common::Result<Vec<Answer>> {
+    let mut answers = vec![];
+    for line in common::Line::all(s) {
+        let text = line.text.trim();
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+        let (lhs, expected) = line.split_once(text, " = ")?;
+        let expected = expected.trim();
+        if expected.is_empty() {
+            return Err(line.error(expected, "missing answer").into());
+        }
+        let mut tokens = lhs.split_ascii_whitespace();
+        let part = match line.require(tokens.next(), "part")? {
+            "part1" => 1,
+            "part2" => 2,
+            token => return Err(line.error(token, "expected part1 or part2").into()),
+        };
+        let mut params = Params::default();
+        for token in tokens {
+            let (name, value) = line.split_once(token, "=")?;
+            params.push(name, value);
+        }
+        answers.push(Answer {
+            part,
+            params,
+            expected: expected.into(),
+        });
+    }
+    Ok(answers)
+}
+
+/// Answers files under `<root>/dayNN/src`, with the day they belong to.
+pub fn answer_files(root: &Path, day: Option<u8>) -> anyhow::Result<Vec<(u8, PathBuf)>> {
+    let mut files = vec![];
+    for d in 1..=25 {
+        if day.is_some_and(|day| day != d) {
+            continue;
+        }
+        let dir = root.join(format!("day{d:02}")).join("src");
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries {
+            let path = entry
+                .with_context(|| format!("reading {}", dir.display()))?
+                .path();
+            if path.extension().is_some_and(|ext| ext == "answers") {
+                files.push((d, path));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[derive(Debug)]
+pub enum Outcome {
+    Ok,
+    Mismatch(String),
+    Skipped(String),
+    Failed(anyhow::Error),
+}
+
+#[derive(Debug)]
+pub struct Check {
+    pub day: u8,
+    pub input: PathBuf,
+    pub answer: Answer,
+    pub outcome: Outcome,
+}
+
+impl std::fmt::Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let input = self.input.file_name().unwrap_or_default().to_string_lossy();
+        write!(f, "day{:02} part{} {input}", self.day, self.answer.part)?;
+        let params = self.answer.params.to_string();
+        if !params.is_empty() {
+            write!(f, " {params}")?;
+        }
+        match &self.outcome {
+            Outcome::Ok => write!(f, ": ok"),
+            Outcome::Mismatch(actual) => {
+                write!(
+                    f,
+                    ": FAILED, expected {} got {actual}",
+                    self.answer.expected
+                )
+            }
+            Outcome::Skipped(reason) => write!(f, ": skipped ({reason})"),
+            Outcome::Failed(err) => write!(f, ": FAILED, {err:#}"),
+        }
+    }
+}
+
+/// Runs every answer in the answers file at `path` against the input of the same name.
+pub fn verify_file(day: u8, path: &Path) -> anyhow::Result<Vec<Check>> {
+    let answers = std::fs::read_to_string(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|s| Ok(parse_answers(&s)?))
+        .with_context(|| format!("reading {}", path.display()))?;
+    let input_path = path.with_extension("txt");
+    let input = std::fs::read_to_string(&input_path)
+        .with_context(|| format!("reading {}", input_path.display()))?;
+    Ok(answers
+        .into_iter()
+        .map(|answer| {
+            let outcome = match run::run(day, answer.part, &input, &answer.params) {
+                Ok(actual) if actual == answer.expected => Outcome::Ok,
+                Ok(actual) => Outcome::Mismatch(actual),
+                Err(err) => match err.downcast_ref::<common::Error>() {
+                    Some(common::Error::Unsupported(reason)) => Outcome::Skipped(reason.clone()),
+                    _ => Outcome::Failed(err),
+                },
+            };
+            Check {
+                day,
+                input: input_path.clone(),
+                answer,
+                outcome,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn parse_answers() -> common::Result<()> {
+        let answers =
+            super::parse_answers("# big.txt\npart1 count=1000 = 807069600\n\npart2 = 42\n")?;
+        assert_eq!(answers.len(), 2);
+        assert_eq!(answers[0].part, 1);
+        assert_eq!(answers[0].params.to_string(), "count=1000");
+        assert_eq!(answers[0].expected, "807069600");
+        assert_eq!((answers[1].part, answers[1].expected.as_str()), (2, "42"));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_answers_malformed() {
+        let Err(common::Error::Parse(err)) = super::parse_answers("part1 = 1\npart3 = 2\n") else {
+            panic!("expected a parse error");
+        };
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 1, "part3"));
+    }
+
+    #[test]
+    fn verify_mismatch() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("aoc-verify-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join("example.txt"), "1abc2\npqr3stu8vwx\n")?;
+        std::fs::write(dir.join("example.answers"), "part1 = 50\npart2 = 51\n")?;
+        let checks = super::verify_file(1, &dir.join("example.answers"))?;
+        std::fs::remove_dir_all(&dir)?;
+        assert!(matches!(checks[0].outcome, super::Outcome::Ok));
+        assert!(matches!(&checks[1].outcome, super::Outcome::Mismatch(actual) if actual == "50"));
+        Ok(())
+    }
+
+    #[test]
+    fn verify_skips_unsupported() -> anyhow::Result<()> {
+        let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+        let files = super::answer_files(&root, Some(20))?;
+        let checks = super::verify_file(20, &files[0].1)?;
+        assert!(matches!(checks[0].outcome, super::Outcome::Ok));
+        assert!(matches!(checks[1].outcome, super::Outcome::Skipped(_)));
+        Ok(())
+    }
+}
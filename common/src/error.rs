@@ -0,0 +1,231 @@
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Parse(ParseError),
+    /// The input parsed, but the solver cannot work with it (or with its parameters).
+    Invalid(String),
+    /// The solver does not compute this answer, e.g. it was found by hand.
+    Unsupported(String),
+    /// The part takes no parameter by this name.
+    UnknownParam(String),
+    /// The solver's own error about line `line` of the input, to be downcast by callers that
+    /// know its type.
+    AtLine {
+        line: usize,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn unknown_param(name: &str) -> Self {
+        Self::UnknownParam(name.into())
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Parse(e) => write!(f, "{e}"),
+            Self::Invalid(s) => write!(f, "{s}"),
+            Self::Unsupported(s) => write!(f, "unsupported: {s}"),
+            Self::UnknownParam(name) => write!(f, "unknown parameter: {name}"),
+            Self::AtLine { line, source } => write!(f, "line {line}: {source}"),
+        }
+    }
+}
+
+// `Io`, `Parse` and `AtLine` display the wrapped error itself, so they pass on its source
+// rather than repeating it.
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => e.source(),
+            Self::Parse(e) => e.source(),
+            Self::AtLine { source, .. } => source.source(),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+/// Malformed input. `line` and `column` count from 1; `line` is 0 when the error was raised
+/// on a fragment that is not yet placed in the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    pub message: String,
+}
+
+impl ParseError {
+    /// Moves an error raised while parsing `inner` to its position within `outer`.
+    pub fn within(mut self, outer: &str, inner: &str) -> Self {
+        self.column += char_offset(outer, inner);
+        self
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line > 0 {
+            write!(f, "line {}, ", self.line)?;
+        }
+        write!(
+            f,
+            "column {}: {}: {:?}",
+            self.column, self.message, self.text
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Number of chars between the start of `base` and `at`, if `at` points into `base`.
+fn char_offset(base: &str, at: &str) -> usize {
+    let (base_ptr, at_ptr) = (base.as_ptr() as usize, at.as_ptr() as usize);
+    if base_ptr <= at_ptr && at_ptr <= base_ptr + base.len() {
+        let offset = at_ptr - base_ptr;
+        if base.is_char_boundary(offset) {
+            return base[..offset].chars().count();
+        }
+    }
+    0
+}
+
+/// A line of input being parsed. Errors about slices of `text` point at the slice.
+#[derive(Debug, Clone, Copy)]
+pub struct Line<'a> {
+    pub number: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    pub fn new(number: usize, text: &'a str) -> Self {
+        Self { number, text }
+    }
+
+    /// Lines of `input`, numbered from 1.
+    pub fn all(input: &'a str) -> impl Iterator<Item = Line<'a>> {
+        input
+            .lines()
+            .enumerate()
+            .map(|(i, text)| Self::new(i + 1, text))
+    }
+
+    pub fn error(&self, at: &str, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: self.number,
+            column: char_offset(self.text, at) + 1,
+            text: at.into(),
+            message: message.into(),
+        }
+    }
+
+    pub fn split_once(
+        &self,
+        s: &'a str,
+        delim: &str,
+    ) -> std::result::Result<(&'a str, &'a str), ParseError> {
+        s.split_once(delim)
+            .ok_or_else(|| self.error(s, format!("expected {delim:?}")))
+    }
+
+    /// Parses a single value out of `s`, ignoring surrounding whitespace.
+    pub fn parse<T>(&self, s: &str) -> std::result::Result<T, ParseError>
+    where
+        T: std::str::FromStr,
+        <T as std::str::FromStr>::Err: std::fmt::Display,
+    {
+        let s = s.trim();
+        s.parse().map_err(|e| {
+            let type_name = std::any::type_name::<T>().rsplit("::").next().unwrap();
+            self.error(s, format!("cannot parse {type_name} ({e})"))
+        })
+    }
+
+    /// Parses `s` with a parser that reports its own positions, relative to `s`.
+    pub fn parse_inner<T>(&self, s: &str) -> std::result::Result<T, ParseError>
+    where
+        T: std::str::FromStr<Err = ParseError>,
+    {
+        s.parse().map_err(|e: ParseError| ParseError {
+            line: self.number,
+            ..e.within(self.text, s)
+        })
+    }
+
+    /// Unwraps the next token, or reports what was missing at the end of the line.
+    pub fn require(
+        &self,
+        token: Option<&'a str>,
+        what: &str,
+    ) -> std::result::Result<&'a str, ParseError> {
+        token.ok_or_else(|| self.error(&self.text[self.text.len()..], format!("missing {what}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Line;
+
+    #[derive(Debug)]
+    struct Pair(i64, i64);
+
+    impl std::str::FromStr for Pair {
+        type Err = super::ParseError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let line = Line::new(0, s);
+            let (a, b) = line.split_once(s, ",")?;
+            Ok(Pair(line.parse(a)?, line.parse(b)?))
+        }
+    }
+
+    #[test]
+    fn positions() {
+        let line = Line::new(7, "abc: 12, 3x");
+        let (_, rest) = line.split_once(line.text, ":").unwrap();
+        let (_, b) = line.split_once(rest, ",").unwrap();
+        let err = line.parse::<i64>(b).unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (7, 10, "3x"));
+        assert_eq!(
+            err.to_string(),
+            "line 7, column 10: cannot parse i64 (invalid digit found in string): \"3x\""
+        );
+
+        let err = line.split_once(rest, ";").unwrap_err();
+        assert_eq!((err.column, err.text.as_str()), (5, " 12, 3x"));
+
+        let err = line
+            .require(rest.split(',').nth(2), "third value")
+            .unwrap_err();
+        assert_eq!(
+            (err.column, err.message.as_str()),
+            (12, "missing third value")
+        );
+    }
+
+    #[test]
+    fn nested_positions() {
+        let line = Line::new(2, "pairs 1,2 3,y");
+        let err = line.parse_inner::<Pair>(&line.text[10..]).unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 13, "y"));
+        let Pair(a, b) = line.parse_inner(&line.text[6..9]).unwrap();
+        assert_eq!((a, b), (1, 2));
+    }
+}
@@ -0,0 +1,116 @@
+mod error;
+
+pub use error::{Error, Line, ParseError, Result};
+
+/// Extra inputs a solver takes besides the puzzle text, settable by name.
+pub trait Params: Default {
+    fn set(&mut self, name: &str, value: &str) -> Result<()>;
+}
+
+impl Params for () {
+    fn set(&mut self, name: &str, _: &str) -> Result<()> {
+        Err(Error::unknown_param(name))
+    }
+}
+
+/// Parses a parameter value, naming the parameter on failure.
+pub fn parse_param<T>(name: &str, value: &str) -> Result<T>
+where
+    T: std::str::FromStr,
+    <T as std::str::FromStr>::Err: std::fmt::Display,
+{
+    value
+        .trim()
+        .parse()
+        .map_err(|e| Error::Invalid(format!("parameter {name}={value}: {e}")))
+}
+
+pub trait Solver {
+    type Params1: Params;
+    type Params2: Params;
+    type Answer1: std::fmt::Display;
+    type Answer2: std::fmt::Display;
+
+    fn part1(input: &str, params: &Self::Params1) -> Result<Self::Answer1>;
+    fn part2(input: &str, params: &Self::Params2) -> Result<Self::Answer2>;
+}
+
+fn build_params<P: Params>(params: &[(&str, &str)]) -> Result<P> {
+    let mut result = P::default();
+    for (name, value) in params {
+        result.set(name, value)?;
+    }
+    Ok(result)
+}
+
+/// Runs one part of `S` with `name=value` parameters and renders the answer.
+pub fn run<S: Solver>(part: u8, input: &str, params: &[(&str, &str)]) -> Result<String> {
+    match part {
+        1 => Ok(S::part1(input, &build_params(params)?)?.to_string()),
+        2 => Ok(S::part2(input, &build_params(params)?)?.to_string()),
+        _ => Err(Error::Invalid(format!("no such part: {part}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Line};
+
+    struct Sum;
+
+    #[derive(Default)]
+    struct Offset {
+        offset: i64,
+    }
+
+    impl super::Params for Offset {
+        fn set(&mut self, name: &str, value: &str) -> super::Result<()> {
+            match name {
+                "offset" => self.offset = super::parse_param(name, value)?,
+                _ => return Err(Error::unknown_param(name)),
+            }
+            Ok(())
+        }
+    }
+
+    impl super::Solver for Sum {
+        type Params1 = ();
+        type Params2 = Offset;
+        type Answer1 = i64;
+        type Answer2 = i64;
+
+        fn part1(input: &str, _: &()) -> super::Result<i64> {
+            let mut total = 0;
+            for line in Line::all(input) {
+                for token in line.text.split_ascii_whitespace() {
+                    total += line.parse::<i64>(token)?;
+                }
+            }
+            Ok(total)
+        }
+
+        fn part2(input: &str, params: &Offset) -> super::Result<i64> {
+            Ok(Self::part1(input, &())? + params.offset)
+        }
+    }
+
+    #[test]
+    fn run() -> super::Result<()> {
+        assert_eq!(super::run::<Sum>(1, "1 2 3", &[])?, "6");
+        assert_eq!(super::run::<Sum>(2, "1 2 3", &[("offset", "10")])?, "16");
+        assert!(matches!(
+            super::run::<Sum>(1, "1 2 3", &[("offset", "10")]),
+            Err(Error::UnknownParam(name)) if name == "offset"
+        ));
+        assert!(super::run::<Sum>(3, "1 2 3", &[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn run_parse_error() {
+        let Err(Error::Parse(err)) = super::run::<Sum>(1, "1 2\n3 x4 5", &[]) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 3, "x4"));
+    }
+}
@@ -1,57 +1,243 @@
 use std::io::BufRead;
 
-pub fn part1<R: std::io::Read>(reader: R) -> i64 {
-    let answer = std::io::BufReader::new(reader)
-        .lines()
-        .map(|line| {
-            let number_str: String = line
-                .unwrap()
-                .chars()
-                .filter(|c| c.is_ascii_digit())
-                .collect();
-            let number_str: String = number_str[..1]
-                .chars()
-                .chain(number_str[number_str.len() - 1..].chars())
-                .collect();
-            number_str.parse::<i64>().unwrap()
-        })
-        .sum::<i64>();
-    answer
+const NONE: u32 = u32::MAX;
+
+/// A token found in a line; `start..end` are byte offsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pub start: usize,
+    pub end: usize,
+    pub value: u32,
+}
+
+/// Finds digit tokens in calibration lines with a single Aho–Corasick automaton, so each line
+/// is read once however many tokens there are. Tokens may overlap, as in "twone".
+#[derive(Debug, Clone)]
+pub struct Decoder {
+    /// Transitions for every state and byte, failure links already followed.
+    delta: Vec<[u32; 256]>,
+    /// The token ending at each state, as (length, value).
+    output: Vec<Option<(u32, u32)>>,
+    /// The nearest state on the failure chain that has an output.
+    dict: Vec<u32>,
 }
 
-pub fn part2<R: std::io::Read>(reader: R) -> i64 {
-    std::io::BufReader::new(reader)
-        .lines()
-        .map(|line| {
-            let line = line.unwrap();
-            let mut min_match: (i64, i64) = (999999999, -1);
-            let mut max_match: (i64, i64) = (-1, -1);
-            for (i, pats) in vec![
-                // (0, vec!["0"]),
-                (1, vec!["1", "one"]),
-                (2, vec!["2", "two"]),
-                (3, vec!["3", "three"]),
-                (4, vec!["4", "four"]),
-                (5, vec!["5", "five"]),
-                (6, vec!["6", "six"]),
-                (7, vec!["7", "seven"]),
-                (8, vec!["8", "eight"]),
-                (9, vec!["9", "nine"]),
-            ] {
-                for pat in pats {
-                    if let Some(pos) = line.find(pat) {
-                        min_match = std::cmp::min(min_match, (pos.try_into().unwrap(), i.into()));
-                    };
-                    if let Some(pos) = line.rfind(pat) {
-                        max_match = std::cmp::max(max_match, (pos.try_into().unwrap(), i.into()));
-                    }
+impl Decoder {
+    /// Builds a decoder for `tokens`. A token listed twice keeps its last value.
+    pub fn new<'a>(tokens: impl IntoIterator<Item = (&'a str, u32)>) -> common::Result<Self> {
+        let mut delta = vec![[NONE; 256]];
+        let mut output = vec![None];
+        for (token, value) in tokens {
+            if token.is_empty() {
+                return Err(common::Error::Invalid("empty token".into()));
+            }
+            let mut state = 0;
+            for &b in token.as_bytes() {
+                if delta[state][usize::from(b)] == NONE {
+                    delta[state][usize::from(b)] = delta.len().try_into().unwrap();
+                    delta.push([NONE; 256]);
+                    output.push(None);
+                }
+                state = delta[state][usize::from(b)] as usize;
+            }
+            output[state] = Some((token.len().try_into().unwrap(), value));
+        }
+
+        let mut fail = vec![0; delta.len()];
+        let mut dict = vec![NONE; delta.len()];
+        let mut queue = std::collections::VecDeque::from([0]);
+        while let Some(state) = queue.pop_front() {
+            let fallback = if state == 0 {
+                [0; 256]
+            } else {
+                delta[fail[state]]
+            };
+            for (next, &f) in delta[state].iter_mut().zip(&fallback) {
+                if *next == NONE {
+                    *next = f;
+                    continue;
                 }
+                let (next, f) = (*next as usize, f as usize);
+                fail[next] = f;
+                dict[next] = if output[f].is_some() {
+                    f as u32
+                } else {
+                    dict[f]
+                };
+                queue.push_back(next);
             }
-            let number_str = format!("{}{}", min_match.1, max_match.1);
-            // eprintln!("{}", number_str);
-            number_str.parse::<i64>().unwrap()
+        }
+        Ok(Self {
+            delta,
+            output,
+            dict,
         })
-        .sum::<i64>()
+    }
+
+    /// The digits 0 to 9.
+    pub fn digits() -> Self {
+        Self::new(DIGITS.iter().copied()).unwrap()
+    }
+
+    /// The digits plus `words`, which are usually the digits spelled out.
+    pub fn with_words<'a>(words: impl IntoIterator<Item = (&'a str, u32)>) -> common::Result<Self> {
+        Self::new(DIGITS.iter().copied().chain(words))
+    }
+
+    /// The digits plus "one" to "nine", as in the puzzle.
+    pub fn english() -> Self {
+        Self::with_words(ENGLISH.iter().copied()).unwrap()
+    }
+
+    fn longest(&self, state: usize) -> u32 {
+        if self.output[state].is_some() {
+            state as u32
+        } else {
+            self.dict[state]
+        }
+    }
+
+    /// Every token in `line`, ordered by where it ends, longest first for a shared end.
+    pub fn matches<'a>(&'a self, line: &'a [u8]) -> impl Iterator<Item = Match> + 'a {
+        let mut state = 0;
+        line.iter().enumerate().flat_map(move |(i, &b)| {
+            state = self.delta[state][usize::from(b)] as usize;
+            let longest = Some(self.longest(state)).filter(|&s| s != NONE);
+            std::iter::successors(longest, |&s| {
+                Some(self.dict[s as usize]).filter(|&s| s != NONE)
+            })
+            .map(move |s| {
+                let (len, value) = self.output[s as usize].unwrap();
+                Match {
+                    start: i + 1 - len as usize,
+                    end: i + 1,
+                    value,
+                }
+            })
+        })
+    }
+
+    /// The first token's value times ten plus the last's, where the first token is the one
+    /// starting earliest and the last is the one ending latest, preferring longer tokens on
+    /// ties. `None` if the line has no tokens.
+    pub fn calibration_value(&self, line: &[u8]) -> Option<i64> {
+        let mut state = 0;
+        let mut first: Option<(usize, u32)> = None;
+        let mut last = None;
+        for (i, &b) in line.iter().enumerate() {
+            state = self.delta[state][usize::from(b)] as usize;
+            let longest = self.longest(state);
+            if longest == NONE {
+                continue;
+            }
+            let (len, value) = self.output[longest as usize].unwrap();
+            let start = i + 1 - len as usize;
+            if first.is_none_or(|(first_start, _)| start <= first_start) {
+                first = Some((start, value));
+            }
+            last = Some(value);
+        }
+        Some(i64::from(first?.1) * 10 + i64::from(last?))
+    }
+
+    /// Sums the calibration values of every line, reading one line at a time.
+    pub fn sum<R: BufRead>(&self, mut reader: R) -> common::Result<i64> {
+        let mut buf = vec![];
+        let mut sum = 0;
+        for number in 1.. {
+            buf.clear();
+            if reader.read_until(b'\n', &mut buf)? == 0 {
+                break;
+            }
+            let line = buf.strip_suffix(b"\n").unwrap_or(&buf);
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            sum += self.calibration_value(line).ok_or_else(|| {
+                let text = String::from_utf8_lossy(line);
+                let line = common::Line::new(number, &text);
+                line.error(line.text, "no digits")
+            })?;
+        }
+        Ok(sum)
+    }
+}
+
+const DIGITS: [(&str, u32); 10] = [
+    ("0", 0),
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
+];
+
+const ENGLISH: [(&str, u32); 9] = [
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
+
+pub fn part1<R: std::io::Read>(reader: R) -> common::Result<i64> {
+    Decoder::digits().sum(std::io::BufReader::new(reader))
+}
+
+pub fn part2<R: std::io::Read>(reader: R) -> common::Result<i64> {
+    Decoder::english().sum(std::io::BufReader::new(reader))
+}
+
+/// Spelled-out digits for part 2 as `word:value,...`; "one" to "nine" unless set.
+#[derive(Debug, Clone, Default)]
+pub struct Words {
+    pub words: Option<Vec<(String, u32)>>,
+}
+
+impl common::Params for Words {
+    fn set(&mut self, name: &str, value: &str) -> common::Result<()> {
+        match name {
+            "words" => {
+                let mut words = vec![];
+                for word in value.split(',') {
+                    let (word, value) = word.split_once(':').ok_or_else(|| {
+                        common::Error::Invalid(format!("parameter {name}: expected word:value"))
+                    })?;
+                    words.push((word.trim().to_string(), common::parse_param(name, value)?));
+                }
+                self.words = Some(words);
+            }
+            _ => return Err(common::Error::unknown_param(name)),
+        }
+        Ok(())
+    }
+}
+
+pub struct Day01;
+
+impl common::Solver for Day01 {
+    type Params1 = ();
+    type Params2 = Words;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn part1(input: &str, _: &()) -> common::Result<i64> {
+        part1(input.as_bytes())
+    }
+
+    fn part2(input: &str, params: &Words) -> common::Result<i64> {
+        let Some(words) = &params.words else {
+            return part2(input.as_bytes());
+        };
+        let words = words.iter().map(|(word, value)| (word.as_str(), *value));
+        Decoder::with_words(words)?.sum(input.as_bytes())
+    }
 }
 
 #[cfg(test)]
@@ -75,28 +261,59 @@ zoneight234
 ";
 
     #[test]
-    fn part1_example() {
-        let answer = super::part1(std::io::Cursor::new(EXAMPLE1));
+    fn part1_example() -> common::Result<()> {
+        let answer = super::part1(std::io::Cursor::new(EXAMPLE1))?;
         assert_eq!(answer, 142);
+        Ok(())
     }
 
     #[test]
-    fn part1() {
+    fn part1() -> common::Result<()> {
         let input = include_str!("big_input.txt");
-        let answer = super::part1(std::io::Cursor::new(input));
+        let answer = super::part1(std::io::Cursor::new(input))?;
         assert_eq!(answer, 54601);
+        Ok(())
+    }
+
+    #[test]
+    fn part1_no_digits() {
+        let Err(common::Error::Parse(err)) = super::part1("1a\nabc\n".as_bytes()) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!((err.line, err.text.as_str()), (2, "abc"));
     }
 
     #[test]
-    fn part2_example() {
-        let answer = super::part2(std::io::Cursor::new(EXAMPLE2));
+    fn part2_example() -> common::Result<()> {
+        let answer = super::part2(std::io::Cursor::new(EXAMPLE2))?;
         assert_eq!(answer, 281);
+        Ok(())
+    }
+
+    #[test]
+    fn overlapping_matches() {
+        let decoder = super::Decoder::english();
+        let values = decoder
+            .matches(b"xtwone3")
+            .map(|m| (m.start, m.value))
+            .collect::<Vec<_>>();
+        assert_eq!(values, [(1, 2), (3, 1), (6, 3)]);
+        assert_eq!(decoder.calibration_value(b"xtwone"), Some(21));
+    }
+
+    #[test]
+    fn custom_words() -> common::Result<()> {
+        let decoder = super::Decoder::with_words([("zero", 0), ("un", 1), ("deux", 2)])?;
+        assert_eq!(decoder.sum("zerodeux\nundeuxzer\n".as_bytes())?, 2 + 12);
+        assert_eq!(decoder.calibration_value(b"trois"), None);
+        Ok(())
     }
 
     #[test]
-    fn part2() {
+    fn part2() -> common::Result<()> {
         let input = include_str!("big_input.txt");
-        let answer = super::part2(std::io::Cursor::new(input));
+        let answer = super::part2(std::io::Cursor::new(input))?;
         assert_eq!(answer, 54078);
+        Ok(())
     }
 }
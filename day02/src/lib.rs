@@ -1,77 +1,286 @@
+use std::collections::BTreeMap;
 use std::io::BufRead;
 
-pub fn part1<R: std::io::Read>(reader: R) -> i64 {
+/// Cubes shown in one handful, by color.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Reveal {
+    pub counts: BTreeMap<String, i64>,
+}
+
+impl Reveal {
+    pub fn count(&self, color: &str) -> i64 {
+        self.counts.get(color).copied().unwrap_or(0)
+    }
+
+    /// Whether the bag holds at least this many cubes of every color.
+    pub fn fits_in(&self, bag: &Bag) -> bool {
+        self.counts
+            .iter()
+            .all(|(color, &count)| count <= bag.count(color))
+    }
+}
+
+impl std::str::FromStr for Reveal {
+    type Err = common::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = common::Line::new(0, s);
+        let mut counts = BTreeMap::new();
+        for cubes in s.split(',') {
+            let (count, color) = line.split_once(cubes.trim(), " ")?;
+            let count = line.parse::<i64>(count)?;
+            *counts.entry(color.trim().to_string()).or_default() += count;
+        }
+        Ok(Self { counts })
+    }
+}
+
+/// How many cubes of each color a bag holds; colors not listed have none.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bag {
+    pub counts: BTreeMap<String, i64>,
+}
+
+impl Bag {
+    pub fn new<'a>(counts: impl IntoIterator<Item = (&'a str, i64)>) -> Self {
+        Self {
+            counts: counts
+                .into_iter()
+                .map(|(color, count)| (color.to_string(), count))
+                .collect(),
+        }
+    }
+
+    /// The bag from the puzzle: 12 red, 13 green and 14 blue cubes.
+    pub fn puzzle() -> Self {
+        Self::new([("red", 12), ("green", 13), ("blue", 14)])
+    }
+
+    pub fn count(&self, color: &str) -> i64 {
+        self.counts.get(color).copied().unwrap_or(0)
+    }
+
+    /// The product of the counts of `colors`.
+    pub fn power(&self, colors: &[&str]) -> i64 {
+        colors.iter().map(|color| self.count(color)).product()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub id: i64,
+    pub reveals: Vec<Reveal>,
+}
+
+impl Game {
+    /// Whether every reveal in the game could have come from `bag`.
+    pub fn is_possible(&self, bag: &Bag) -> bool {
+        self.reveals.iter().all(|reveal| reveal.fits_in(bag))
+    }
+
+    /// The smallest bag every reveal fits in.
+    pub fn min_bag(&self) -> Bag {
+        let mut bag = Bag::default();
+        for reveal in &self.reveals {
+            for (color, &count) in &reveal.counts {
+                let max_count = bag.counts.entry(color.clone()).or_default();
+                *max_count = (*max_count).max(count);
+            }
+        }
+        bag
+    }
+}
+
+impl std::str::FromStr for Game {
+    type Err = common::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = common::Line::new(0, s);
+        let (left, right) = line.split_once(s, ":")?;
+        let (_, id) = line.split_once(left, " ")?;
+        let id = line.parse(id)?;
+        let reveals = right
+            .split(';')
+            .map(|reveal| line.parse_inner(reveal))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { id, reveals })
+    }
+}
+
+pub fn read_games<R: std::io::Read>(reader: R) -> common::Result<Vec<Game>> {
+    let mut games = vec![];
+    for (i, text) in std::io::BufReader::new(reader).lines().enumerate() {
+        let text = text?;
+        let line = common::Line::new(i + 1, &text);
+        games.push(line.parse_inner(line.text)?);
+    }
+    Ok(games)
+}
+
+/// Sum of the ids of the games possible with `bag`.
+pub fn possible_ids_sum(games: &[Game], bag: &Bag) -> i64 {
+    games
+        .iter()
+        .filter(|game| game.is_possible(bag))
+        .map(|game| game.id)
+        .sum()
+}
+
+/// Sum over the games of the power of their smallest bag, over `colors`. Fails if a game
+/// shows any other color.
+pub fn min_bag_power_sum(games: &[Game], colors: &[&str]) -> common::Result<i64> {
     let mut answer = 0;
-    for line in std::io::BufReader::new(reader).lines() {
-        let line = line.unwrap();
-        let (left, right) = line.split_once(':').unwrap();
-        let (_, game_id) = left.split_once(' ').unwrap();
-        let game_id = game_id.parse::<i64>().unwrap();
-        let mut valid = true;
-        for reveal_description in right.split(';') {
-            for set_description in reveal_description.split(',') {
-                let (count, type_) = set_description.trim().split_once(' ').unwrap();
-                let count = count.trim().parse::<i64>().unwrap();
-                let type_ = type_.trim();
-                if (type_ == "red" && count > 12)
-                    || (type_ == "green" && count > 13)
-                    || (type_ == "blue" && count > 14)
-                {
-                    valid = false;
+    for game in games {
+        let bag = game.min_bag();
+        if let Some(color) = bag
+            .counts
+            .keys()
+            .find(|color| !colors.contains(&color.as_str()))
+        {
+            return Err(common::Error::Invalid(format!(
+                "game {}: unknown color {color}",
+                game.id
+            )));
+        }
+        answer += bag.power(colors);
+    }
+    Ok(answer)
+}
+
+const COLORS: [&str; 3] = ["red", "green", "blue"];
+
+pub fn part1<R: std::io::Read>(reader: R) -> common::Result<i64> {
+    Ok(possible_ids_sum(&read_games(reader)?, &Bag::puzzle()))
+}
+
+pub fn part2<R: std::io::Read>(reader: R) -> common::Result<i64> {
+    min_bag_power_sum(&read_games(reader)?, &COLORS)
+}
+
+fn parse_list<'a>(name: &str, value: &'a str) -> common::Result<Vec<&'a str>> {
+    let list = value.split(',').map(str::trim).collect::<Vec<_>>();
+    if list.iter().any(|item| item.is_empty()) {
+        return Err(common::Error::Invalid(format!(
+            "parameter {name}={value}: empty item"
+        )));
+    }
+    Ok(list)
+}
+
+/// The bag for part 1 as `color:count,...`; 12 red, 13 green and 14 blue unless set.
+#[derive(Debug, Clone, Default)]
+pub struct BagParam {
+    pub bag: Option<Bag>,
+}
+
+impl common::Params for BagParam {
+    fn set(&mut self, name: &str, value: &str) -> common::Result<()> {
+        match name {
+            "bag" => {
+                let mut bag = Bag::default();
+                for item in parse_list(name, value)? {
+                    let (color, count) = item.split_once(':').ok_or_else(|| {
+                        common::Error::Invalid(format!("parameter {name}: expected color:count"))
+                    })?;
+                    bag.counts
+                        .insert(color.trim().into(), common::parse_param(name, count)?);
                 }
+                self.bag = Some(bag);
             }
+            _ => return Err(common::Error::unknown_param(name)),
         }
-        // println!("game {game_id}: {valid}");
-        if valid {
-            answer += game_id;
-        }
+        Ok(())
     }
-    answer
 }
 
-pub fn part2<R: std::io::Read>(reader: R) -> i64 {
-    let mut answer = 0;
-    for line in std::io::BufReader::new(reader).lines() {
-        let line = line.unwrap();
-        let (_, right) = line.split_once(':').unwrap();
-        let mut map = vec![("red", 0), ("blue", 0), ("green", 0)]
-            .into_iter()
-            .collect::<std::collections::HashMap<&str, i64>>();
-        for reveal_description in right.split(';') {
-            for set_description in reveal_description.split(',') {
-                let (count, type_) = set_description.trim().split_once(' ').unwrap();
-                let count = count.trim().parse::<i64>().unwrap();
-                let type_ = type_.trim();
-                *map.get_mut(type_).unwrap() = std::cmp::max(map[type_], count);
+/// The colors whose counts multiply into the power for part 2; red, green and blue unless set.
+#[derive(Debug, Clone, Default)]
+pub struct Colors {
+    pub colors: Option<Vec<String>>,
+}
+
+impl common::Params for Colors {
+    fn set(&mut self, name: &str, value: &str) -> common::Result<()> {
+        match name {
+            "colors" => {
+                let colors = parse_list(name, value)?;
+                self.colors = Some(colors.into_iter().map(String::from).collect());
             }
+            _ => return Err(common::Error::unknown_param(name)),
         }
-        let power = map.values().product::<i64>();
-        // println!("game {game_id}: {power}");
-        answer += power;
+        Ok(())
+    }
+}
+
+pub struct Day02;
+
+impl common::Solver for Day02 {
+    type Params1 = BagParam;
+    type Params2 = Colors;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn part1(input: &str, params: &BagParam) -> common::Result<i64> {
+        let bag = params.bag.clone().unwrap_or_else(Bag::puzzle);
+        Ok(possible_ids_sum(&read_games(input.as_bytes())?, &bag))
+    }
+
+    fn part2(input: &str, params: &Colors) -> common::Result<i64> {
+        let colors = match &params.colors {
+            Some(colors) => colors.iter().map(String::as_str).collect(),
+            None => COLORS.to_vec(),
+        };
+        min_bag_power_sum(&read_games(input.as_bytes())?, &colors)
     }
-    answer
 }
 
 #[cfg(test)]
 mod tests {
     #[test]
-    fn part1_example() {
+    fn part1_example() -> common::Result<()> {
         let input = std::io::Cursor::new(include_str!("example1.txt"));
-        assert_eq!(super::part1(input), 8);
+        assert_eq!(super::part1(input)?, 8);
+        Ok(())
     }
     #[test]
-    fn part1() {
+    fn part1() -> common::Result<()> {
         let input = std::io::Cursor::new(include_str!("big.txt"));
-        assert_eq!(super::part1(input), 2771);
+        assert_eq!(super::part1(input)?, 2771);
+        Ok(())
+    }
+    #[test]
+    fn part1_malformed() {
+        let input = "Game 1: 3 blue, 4 red\nGame 2: 1 blue; two green\n";
+        let Err(common::Error::Parse(err)) = super::part1(input.as_bytes()) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 17, "two"));
+    }
+    #[test]
+    fn game_queries() -> common::Result<()> {
+        let game: super::Game = "Game 7: 3 teal, 1 red; 2 red, 4 teal, 5 gold".parse()?;
+        assert_eq!((game.id, game.reveals.len()), (7, 2));
+        assert_eq!(game.reveals[1].count("gold"), 5);
+        let bag = game.min_bag();
+        assert_eq!(bag, super::Bag::new([("teal", 4), ("red", 2), ("gold", 5)]));
+        assert_eq!(bag.power(&["teal", "red"]), 8);
+        assert!(game.is_possible(&bag));
+        assert!(!game.is_possible(&super::Bag::new([("teal", 4), ("red", 2)])));
+        let games = [game];
+        assert!(super::min_bag_power_sum(&games, &["teal", "red"]).is_err());
+        assert_eq!(super::min_bag_power_sum(&games, &["teal", "red", "gold"])?, 40);
+        Ok(())
     }
     #[test]
-    fn part2_example() {
+    fn part2_example() -> common::Result<()> {
         let input = std::io::Cursor::new(include_str!("example1.txt"));
-        assert_eq!(super::part2(input), 2286);
+        assert_eq!(super::part2(input)?, 2286);
+        Ok(())
     }
     #[test]
-    fn part2() {
+    fn part2() -> common::Result<()> {
         let input = std::io::Cursor::new(include_str!("big.txt"));
-        assert_eq!(super::part2(input), 70924);
+        assert_eq!(super::part2(input)?, 70924);
+        Ok(())
     }
 }
@@ -1,113 +1,234 @@
+use std::collections::{BTreeSet, HashMap};
 use std::io::BufRead;
 
-fn symbol_in_region(board: &[Vec<char>], ra: i64, rb: i64, ca: i64, cb: i64) -> bool {
-    let ra = ra.clamp(0, (board.len() - 1).try_into().unwrap());
-    let rb = rb.clamp(0, (board.len() - 1).try_into().unwrap());
-    let ca = ca.clamp(0, (board[0].len() - 1).try_into().unwrap());
-    let cb = cb.clamp(0, (board[0].len() - 1).try_into().unwrap());
-    for r in ra..=rb {
-        for c in ca..=cb {
-            let val = board[usize::try_from(r).unwrap()][usize::try_from(c).unwrap()];
-            if !val.is_ascii_digit() && val != '.' && !val.is_whitespace() {
-                return true;
+/// A run of digits on one row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Number {
+    pub row: usize,
+    pub col: usize,
+    pub len: usize,
+    pub value: i64,
+}
+
+/// Any cell that is not a digit, `.` or whitespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symbol {
+    pub row: usize,
+    pub col: usize,
+    pub kind: char,
+}
+
+/// An engine schematic with every number linked to the symbols around it, and back.
+#[derive(Debug, Clone, Default)]
+pub struct Schematic {
+    pub numbers: Vec<Number>,
+    pub symbols: Vec<Symbol>,
+    number_symbols: Vec<Vec<usize>>,
+    symbol_numbers: Vec<Vec<usize>>,
+    number_at: HashMap<(usize, usize), usize>,
+}
+
+impl Schematic {
+    pub fn read<R: std::io::Read>(reader: R) -> common::Result<Self> {
+        let mut schematic = Self::default();
+        let mut symbol_at = HashMap::new();
+        let mut ncols = None;
+        for (row, text) in std::io::BufReader::new(reader).lines().enumerate() {
+            let text = text?;
+            let line = common::Line::new(row + 1, text.trim());
+            let cells = line.text.char_indices().collect::<Vec<_>>();
+            if *ncols.get_or_insert(cells.len()) != cells.len() {
+                let message = format!("expected {} columns", ncols.unwrap());
+                return Err(line.error(line.text, message).into());
+            }
+            let mut col = 0;
+            while col < cells.len() {
+                let (start, c) = cells[col];
+                if !c.is_ascii_digit() {
+                    if c != '.' && !c.is_whitespace() {
+                        symbol_at.insert((row, col), schematic.symbols.len());
+                        schematic.symbols.push(Symbol { row, col, kind: c });
+                    }
+                    col += 1;
+                    continue;
+                }
+                let len = cells[col..]
+                    .iter()
+                    .take_while(|(_, c)| c.is_ascii_digit())
+                    .count();
+                let value = line.parse(&line.text[start..start + len])?;
+                for i in col..col + len {
+                    schematic
+                        .number_at
+                        .insert((row, i), schematic.numbers.len());
+                }
+                schematic.numbers.push(Number {
+                    row,
+                    col,
+                    len,
+                    value,
+                });
+                col += len;
             }
         }
-    }
-    false
-}
 
-fn gears_in_region(board: &[Vec<char>], ra: i64, rb: i64, ca: i64, cb: i64) -> Vec<(i64, i64)> {
-    let ra = ra.clamp(0, (board.len() - 1).try_into().unwrap());
-    let rb = rb.clamp(0, (board.len() - 1).try_into().unwrap());
-    let ca = ca.clamp(0, (board[0].len() - 1).try_into().unwrap());
-    let cb = cb.clamp(0, (board[0].len() - 1).try_into().unwrap());
-    let mut rv = vec![];
-    for r in ra..=rb {
-        for c in ca..=cb {
-            let val = board[usize::try_from(r).unwrap()][usize::try_from(c).unwrap()];
-            if val == '*' {
-                rv.push((r, c));
+        schematic.symbol_numbers = vec![vec![]; schematic.symbols.len()];
+        for (n, number) in schematic.numbers.iter().enumerate() {
+            let mut adjacent = vec![];
+            for r in number.row.saturating_sub(1)..=number.row + 1 {
+                for c in number.col.saturating_sub(1)..=number.col + number.len {
+                    if let Some(&s) = symbol_at.get(&(r, c)) {
+                        adjacent.push(s);
+                        schematic.symbol_numbers[s].push(n);
+                    }
+                }
             }
+            schematic.number_symbols.push(adjacent);
         }
+        Ok(schematic)
+    }
+
+    /// Numbers next to at least one symbol.
+    pub fn part_numbers(&self) -> impl Iterator<Item = &Number> {
+        self.numbers
+            .iter()
+            .zip(&self.number_symbols)
+            .filter(|(_, symbols)| !symbols.is_empty())
+            .map(|(number, _)| number)
+    }
+
+    /// Numbers next to a symbol of `kind`, each once.
+    pub fn numbers_touching(&self, kind: char) -> impl Iterator<Item = &Number> {
+        self.numbers
+            .iter()
+            .zip(&self.number_symbols)
+            .filter(move |(_, symbols)| symbols.iter().any(|&s| self.symbols[s].kind == kind))
+            .map(|(number, _)| number)
+    }
+
+    /// Symbols of `kind` next to exactly `neighbors` numbers, with those numbers.
+    pub fn gears(
+        &self,
+        kind: char,
+        neighbors: usize,
+    ) -> impl Iterator<Item = (&Symbol, Vec<&Number>)> {
+        self.symbols
+            .iter()
+            .zip(&self.symbol_numbers)
+            .filter(move |(symbol, numbers)| symbol.kind == kind && numbers.len() == neighbors)
+            .map(|(symbol, numbers)| (symbol, numbers.iter().map(|&n| &self.numbers[n]).collect()))
+    }
+
+    /// Sum over `gears(kind, neighbors)` of the product of each gear's numbers.
+    pub fn gear_ratio_sum(&self, kind: char, neighbors: usize) -> i64 {
+        self.gears(kind, neighbors)
+            .map(|(_, numbers)| numbers.iter().map(|number| number.value).product::<i64>())
+            .sum()
+    }
+
+    /// The kinds of symbol next to the number covering (`row`, `col`), or `None` if no
+    /// number does.
+    pub fn symbols_next_to(&self, row: usize, col: usize) -> Option<BTreeSet<char>> {
+        let &n = self.number_at.get(&(row, col))?;
+        Some(
+            self.number_symbols[n]
+                .iter()
+                .map(|&s| self.symbols[s].kind)
+                .collect(),
+        )
     }
-    rv
 }
 
-pub fn part1<R: std::io::Read>(reader: R) -> i64 {
-    let board: Vec<Vec<char>> = std::io::BufReader::new(reader)
-        .lines()
-        .map(|line| line.unwrap().trim().chars().collect())
-        .collect();
-    let mut answer = 0;
-    for (r, line) in board.iter().enumerate() {
-        let r: i64 = r.try_into().unwrap();
-        let line: String = line.iter().collect();
-        for match_ in regex::Regex::new(r"\d+").unwrap().find_iter(&line) {
-            let ca: i64 = match_.start().try_into().unwrap();
-            let cb: i64 = match_.end().try_into().unwrap();
-            let cb = cb - 1;
-            let number: i64 = match_.as_str().parse().unwrap();
-            let has_symbol = symbol_in_region(&board, r - 1, r + 1, ca - 1, cb + 1);
-            println!("number={number} has_symbol={has_symbol}");
-            if has_symbol {
-                answer += number;
-            }
+pub fn part1<R: std::io::Read>(reader: R) -> common::Result<i64> {
+    let schematic = Schematic::read(reader)?;
+    Ok(schematic.part_numbers().map(|number| number.value).sum())
+}
+
+pub fn part2<R: std::io::Read>(reader: R) -> common::Result<i64> {
+    Ok(Schematic::read(reader)?.gear_ratio_sum('*', 2))
+}
+
+/// Which symbols count as gears for part 2 and how many numbers they need; `*` with
+/// exactly 2 unless set.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Gears {
+    pub symbol: Option<char>,
+    pub neighbors: Option<usize>,
+}
+
+impl common::Params for Gears {
+    fn set(&mut self, name: &str, value: &str) -> common::Result<()> {
+        match name {
+            "symbol" => self.symbol = Some(common::parse_param(name, value)?),
+            "neighbors" => self.neighbors = Some(common::parse_param(name, value)?),
+            _ => return Err(common::Error::unknown_param(name)),
         }
+        Ok(())
     }
-    answer
 }
 
-pub fn part2<R: std::io::Read>(reader: R) -> i64 {
-    let board: Vec<Vec<char>> = std::io::BufReader::new(reader)
-        .lines()
-        .map(|line| line.unwrap().trim().chars().collect())
-        .collect();
-    let mut gear_candidates = std::collections::HashMap::<(i64, i64), Vec<i64>>::new();
-    for (r, line) in board.iter().enumerate() {
-        let r: i64 = r.try_into().unwrap();
-        let line: String = line.iter().collect();
-        for match_ in regex::Regex::new(r"\d+").unwrap().find_iter(&line) {
-            let ca: i64 = match_.start().try_into().unwrap();
-            let cb: i64 = match_.end().try_into().unwrap();
-            let cb = cb - 1;
-            let number: i64 = match_.as_str().parse().unwrap();
-            for gear_position in gears_in_region(&board, r - 1, r + 1, ca - 1, cb + 1) {
-                gear_candidates
-                    .entry(gear_position)
-                    .or_default()
-                    .push(number);
-            }
-        }
+pub struct Day03;
+
+impl common::Solver for Day03 {
+    type Params1 = ();
+    type Params2 = Gears;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn part1(input: &str, _: &()) -> common::Result<i64> {
+        part1(input.as_bytes())
     }
-    let mut answer = 0;
-    for (_, candidates) in gear_candidates {
-        if candidates.len() == 2 {
-            answer += candidates[0] * candidates[1];
-        }
+
+    fn part2(input: &str, params: &Gears) -> common::Result<i64> {
+        let schematic = Schematic::read(input.as_bytes())?;
+        Ok(schematic.gear_ratio_sum(params.symbol.unwrap_or('*'), params.neighbors.unwrap_or(2)))
     }
-    answer
 }
 
 #[cfg(test)]
 mod tests {
     #[test]
-    fn part1_example() {
+    fn part1_example() -> common::Result<()> {
         let input = std::io::Cursor::new(include_str!("example1.txt"));
-        assert_eq!(super::part1(input), 4361);
+        assert_eq!(super::part1(input)?, 4361);
+        Ok(())
     }
     #[test]
-    fn part1() {
+    fn part1() -> common::Result<()> {
         let input = std::io::Cursor::new(include_str!("big.txt"));
-        assert_eq!(super::part1(input), 550064);
+        assert_eq!(super::part1(input)?, 550064);
+        Ok(())
+    }
+    #[test]
+    fn schematic_queries() -> common::Result<()> {
+        let input = "12.3..\n..*..7\n4#5*..\n";
+        let schematic = super::Schematic::read(input.as_bytes())?;
+        let values =
+            |numbers: Vec<&super::Number>| numbers.iter().map(|n| n.value).collect::<Vec<_>>();
+        assert_eq!(values(schematic.numbers_touching('#').collect()), [4, 5]);
+        assert_eq!(values(schematic.part_numbers().collect()), [12, 3, 4, 5]);
+        let gears = schematic.gears('*', 3).collect::<Vec<_>>();
+        assert_eq!(gears.len(), 1);
+        assert_eq!((gears[0].0.row, gears[0].0.col), (1, 2));
+        assert_eq!(schematic.gear_ratio_sum('*', 3), 12 * 3 * 5);
+        assert_eq!(schematic.gear_ratio_sum('*', 2), 0);
+        assert_eq!(schematic.gear_ratio_sum('*', 1), 5);
+        let kinds = schematic.symbols_next_to(2, 2).unwrap();
+        assert_eq!(kinds.into_iter().collect::<String>(), "#*");
+        assert_eq!(schematic.symbols_next_to(1, 5), Some(Default::default()));
+        assert_eq!(schematic.symbols_next_to(0, 4), None);
+        Ok(())
     }
     #[test]
-    fn part2_example() {
+    fn part2_example() -> common::Result<()> {
         let input = std::io::Cursor::new(include_str!("example1.txt"));
-        assert_eq!(super::part2(input), 467835);
+        assert_eq!(super::part2(input)?, 467835);
+        Ok(())
     }
     #[test]
-    fn part2() {
+    fn part2() -> common::Result<()> {
         let input = std::io::Cursor::new(include_str!("big.txt"));
-        assert_eq!(super::part2(input), 85010461);
+        assert_eq!(super::part2(input)?, 85010461);
+        Ok(())
     }
 }
@@ -1,94 +1,242 @@
+use std::collections::{BTreeMap, HashSet};
 use std::io::BufRead;
 
-pub fn part1<R: std::io::Read>(reader: R) -> i64 {
-    let reader = std::io::BufReader::new(reader);
-    let mut total_points = 0;
-    for line in reader.lines() {
-        let line = line.unwrap();
-        let (_, card_contents) = line.split_once(':').unwrap();
-        let (winning_numbers, your_hand) = card_contents.split_once('|').unwrap();
-        let winning_numbers = winning_numbers
-            .trim()
-            .split_ascii_whitespace()
-            .map(|s| s.parse::<i64>().unwrap())
-            .collect::<std::collections::HashSet<_>>();
-        let your_hand = your_hand
-            .trim()
-            .split_ascii_whitespace()
-            .map(|s| s.parse::<i64>().unwrap())
-            .collect::<std::collections::HashSet<_>>();
-        let exponent: i64 = winning_numbers
-            .intersection(&your_hand)
-            .count()
-            .try_into()
-            .unwrap();
-        let score = if exponent > 0 {
-            2i64.pow((exponent - 1).try_into().unwrap())
-        } else {
-            0
-        };
-        total_points += score;
+fn parse_numbers(line: &common::Line, s: &str) -> Result<HashSet<i64>, common::ParseError> {
+    s.split_ascii_whitespace()
+        .map(|s| line.parse::<i64>(s))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Card {
+    pub number: i64,
+    pub winning: HashSet<i64>,
+    pub have: HashSet<i64>,
+}
+
+impl Card {
+    fn parse(line: &common::Line) -> Result<Self, common::ParseError> {
+        let (label, card_contents) = line.split_once(line.text, ":")?;
+        let number = line.require(label.split_ascii_whitespace().nth(1), "card number")?;
+        let number = line.parse(number)?;
+        let (winning, have) = line.split_once(card_contents, "|")?;
+        Ok(Self {
+            number,
+            winning: parse_numbers(line, winning)?,
+            have: parse_numbers(line, have)?,
+        })
+    }
+
+    pub fn matches(&self) -> usize {
+        self.winning.intersection(&self.have).count()
+    }
+
+    pub fn points(&self) -> common::Result<i64> {
+        match self.matches() {
+            0 => Ok(0),
+            n => u32::try_from(n - 1)
+                .ok()
+                .and_then(|shift| 1_i64.checked_shl(shift))
+                .filter(|&points| points > 0)
+                .ok_or_else(|| {
+                    let message = format!("card {} scores 2^{} points", self.number, n - 1);
+                    common::Error::Invalid(message)
+                }),
+        }
     }
-    total_points
 }
 
-pub fn part2<R: std::io::Read>(reader: R) -> i64 {
-    let reader = std::io::BufReader::new(reader);
-    let mut total_points = 0;
-    let mut copy_count = std::collections::HashMap::<i64, i64>::new();
-    for (card_number, line) in reader.lines().enumerate() {
-        let card_number: i64 = (card_number + 1).try_into().unwrap();
-        let card_count = 1 + copy_count.get(&card_number).unwrap_or(&0i64);
-        total_points += card_count;
-        let line = line.unwrap();
-        let (_, card_contents) = line.split_once(':').unwrap();
-        let (winning_numbers, your_hand) = card_contents.split_once('|').unwrap();
-        let winning_numbers = winning_numbers
-            .trim()
-            .split_ascii_whitespace()
-            .map(|s| s.parse::<i64>().unwrap())
-            .collect::<std::collections::HashSet<_>>();
-        let your_hand = your_hand
-            .trim()
-            .split_ascii_whitespace()
-            .map(|s| s.parse::<i64>().unwrap())
-            .collect::<std::collections::HashSet<_>>();
-        let match_count: i64 = winning_numbers
-            .intersection(&your_hand)
-            .count()
-            .try_into()
-            .unwrap();
-        for index in (card_number + 1)..(card_number + 1 + match_count) {
-            *copy_count.entry(index).or_insert(0) += card_count;
+/// A pile of scratchcards, ordered by card number. A card with `m` matches wins one copy of
+/// each card numbered from one to `m` above it; numbers missing from the pile win nothing.
+#[derive(Debug, Clone)]
+pub struct Scratchcards {
+    cards: Vec<Card>,
+    copies: Vec<i64>,
+}
+
+impl Scratchcards {
+    pub fn read<R: std::io::Read>(reader: R) -> common::Result<Self> {
+        let mut cards = vec![];
+        for (i, text) in std::io::BufReader::new(reader).lines().enumerate() {
+            let text = text?;
+            cards.push(Card::parse(&common::Line::new(i + 1, &text))?);
         }
+        Self::new(cards)
+    }
+
+    pub fn new(mut cards: Vec<Card>) -> common::Result<Self> {
+        cards.sort_by_key(|card| card.number);
+        if let Some(pair) = cards
+            .windows(2)
+            .find(|pair| pair[0].number == pair[1].number)
+        {
+            let message = format!("duplicate card {}", pair[0].number);
+            return Err(common::Error::Invalid(message));
+        }
+        // Each card adds its copy count to a range of later card numbers, so a running sum
+        // of range starts and ends gives every card's copies in one sweep.
+        let mut delta = BTreeMap::<i64, i64>::new();
+        let mut running = 0;
+        let mut copies = Vec::with_capacity(cards.len());
+        for card in &cards {
+            let matches = i64::try_from(card.matches()).unwrap();
+            let Some(end) = card.number.checked_add(matches + 1) else {
+                let message = format!("card {} wins cards numbered past i64::MAX", card.number);
+                return Err(common::Error::Invalid(message));
+            };
+            let expired = delta.split_off(&(card.number + 1));
+            running += delta.values().sum::<i64>();
+            delta = expired;
+            let count = 1 + running;
+            copies.push(count);
+            if matches > 0 {
+                *delta.entry(card.number + 1).or_default() += count;
+                *delta.entry(end).or_default() -= count;
+            }
+        }
+        Ok(Self { cards, copies })
+    }
+
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    fn index(&self, number: i64) -> Option<usize> {
+        self.cards
+            .binary_search_by_key(&number, |card| card.number)
+            .ok()
+    }
+
+    pub fn card(&self, number: i64) -> Option<&Card> {
+        Some(&self.cards[self.index(number)?])
+    }
+
+    /// How many copies of card `number` end up in the pile, counting the original.
+    pub fn copies(&self, number: i64) -> Option<i64> {
+        Some(self.copies[self.index(number)?])
+    }
+
+    pub fn total_copies(&self) -> i64 {
+        self.copies.iter().sum()
+    }
+
+    pub fn total_points(&self) -> common::Result<i64> {
+        self.cards.iter().try_fold(0_i64, |total, card| {
+            total
+                .checked_add(card.points()?)
+                .ok_or_else(|| common::Error::Invalid("total points overflow i64".into()))
+        })
+    }
+
+    /// Where the copies of card `number` came from: every earlier card that wins it, with
+    /// the copies it contributed. These plus the original add up to `copies(number)`.
+    pub fn copies_from(&self, number: i64) -> Option<Vec<(i64, i64)>> {
+        let end = self.index(number)?;
+        Some(
+            self.cards[..end]
+                .iter()
+                .zip(&self.copies)
+                .filter(|(card, _)| card.number + i64::try_from(card.matches()).unwrap() >= number)
+                .map(|(card, &copies)| (card.number, copies))
+                .collect(),
+        )
+    }
+}
+
+pub fn part1<R: std::io::Read>(reader: R) -> common::Result<i64> {
+    Scratchcards::read(reader)?.total_points()
+}
+
+pub fn part2<R: std::io::Read>(reader: R) -> common::Result<i64> {
+    Ok(Scratchcards::read(reader)?.total_copies())
+}
+
+pub struct Day04;
+
+impl common::Solver for Day04 {
+    type Params1 = ();
+    type Params2 = ();
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn part1(input: &str, _: &()) -> common::Result<i64> {
+        part1(input.as_bytes())
+    }
+
+    fn part2(input: &str, _: &()) -> common::Result<i64> {
+        part2(input.as_bytes())
     }
-    total_points
 }
 
 #[cfg(test)]
 mod tests {
     #[test]
-    fn test_part1_example() {
+    fn test_part1_example() -> common::Result<()> {
         let input = include_str!("example1.txt");
-        let result = super::part1(std::io::Cursor::new(input));
+        let result = super::part1(std::io::Cursor::new(input))?;
         assert_eq!(result, 13);
+        Ok(())
     }
     #[test]
-    fn test_part1_final() {
+    fn test_part1_final() -> common::Result<()> {
         let input = include_str!("big.txt");
-        let result = super::part1(std::io::Cursor::new(input));
+        let result = super::part1(std::io::Cursor::new(input))?;
         assert_eq!(result, 23941);
+        Ok(())
+    }
+    #[test]
+    fn test_out_of_order_with_gaps() -> common::Result<()> {
+        let input = "\
+Card 5: 1 2 | 1 2
+Card 1: 1 2 | 1 9
+Card 2: 1 2 3 | 1 2 3
+Card 7: 1 | 2
+";
+        let cards = super::Scratchcards::read(input.as_bytes())?;
+        let copies = [1, 2, 5, 7].map(|n| cards.copies(n).unwrap());
+        // Card 2 wins cards 3 to 5, of which only 5 exists.
+        assert_eq!(copies, [1, 2, 3, 4]);
+        assert_eq!(cards.copies(3), None);
+        assert_eq!(cards.copies_from(5), Some(vec![(2, 2)]));
+        assert_eq!(cards.copies_from(7), Some(vec![(5, 3)]));
+        assert_eq!(cards.total_copies(), 10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_overflow() -> common::Result<()> {
+        let numbers = |n: i64| (1..=n).map(|i| i.to_string()).collect::<Vec<_>>().join(" ");
+        let card = |n| format!("Card 1: {} | {}\n", numbers(n), numbers(n));
+        assert_eq!(super::part1(card(63).as_bytes())?, 1 << 62);
+        let Err(common::Error::Invalid(message)) = super::part1(card(64).as_bytes()) else {
+            panic!("expected an invalid card");
+        };
+        assert_eq!(message, "card 1 scores 2^63 points");
+        let input = format!("{}{}", card(63), card(63).replace("Card 1", "Card 2"));
+        assert!(matches!(
+            super::part1(input.as_bytes()),
+            Err(common::Error::Invalid(_))
+        ));
+
+        let input = "Card 9223372036854775806: 1 2 | 1 2\n";
+        let Err(common::Error::Invalid(message)) = super::part2(input.as_bytes()) else {
+            panic!("expected an invalid card");
+        };
+        assert!(message.contains("past i64::MAX"), "{message}");
+        Ok(())
     }
     #[test]
-    fn test_part2_example() {
+    fn test_part2_example() -> common::Result<()> {
         let input = include_str!("example1.txt");
-        let result = super::part2(std::io::Cursor::new(input));
+        let result = super::part2(std::io::Cursor::new(input))?;
         assert_eq!(result, 30);
+        Ok(())
     }
     #[test]
-    fn test_part2_final() {
+    fn test_part2_final() -> common::Result<()> {
         let input = include_str!("big.txt");
-        let result = super::part2(std::io::Cursor::new(input));
+        let result = super::part2(std::io::Cursor::new(input))?;
         assert_eq!(result, 5571760);
+        Ok(())
     }
 }
@@ -1,238 +1,561 @@
 #![feature(iter_array_chunks)]
 
+use std::collections::HashMap;
 use std::io::BufRead;
 
-#[derive(Debug, Clone, Copy, Hash)]
-struct Range(i64, i64);
+/// The half-open interval `[.0, .1)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Range(pub i64, pub i64);
 
-#[derive(Debug, Clone, Copy)]
-struct ShuffleOp {
-    source_start: i64,
-    source_end: i64,
-    destination_start: i64,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Piece {
+    start: i64,
+    offset: i64,
 }
 
-impl ShuffleOp {
-    fn map(&self, value: i64) -> Option<i64> {
-        // println!("mapping {value} with {self:?}");
-        if value >= self.source_start && value < self.source_end {
-            Some(value - self.source_start + self.destination_start)
-        } else {
-            None
+/// A map that adds an offset to each number, the offset being constant on each piece of the
+/// number line. Pieces are sorted, the first starts at `i64::MIN`, the last runs up to
+/// `i64::MAX` and neighbouring pieces have different offsets, so equal maps compare equal.
+///
+/// Every map is built from almanac maps whose numbers `parse_input` checked to be
+/// non-negative with range ends that fit in an `i64`. So a piece with a non-zero offset lies
+/// within `0..=i64::MAX` and so does its image, and composing or inverting maps cannot
+/// overflow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PiecewiseMap {
+    pieces: Vec<Piece>,
+}
+
+impl PiecewiseMap {
+    pub fn identity() -> Self {
+        Self {
+            pieces: vec![Piece {
+                start: i64::MIN,
+                offset: 0,
+            }],
         }
     }
 
-    fn map_range(
-        &self,
-        Range(range_start, range_end): Range,
-    ) -> (Option<Range>, Option<Range>, Option<Range>) {
-        let source_start = self.source_start;
-        let source_end = self.source_end;
+    fn normalized(mut pieces: Vec<Piece>) -> Self {
+        pieces.sort_by_key(|piece| piece.start);
+        let mut result: Vec<Piece> = Vec::with_capacity(pieces.len());
+        for piece in pieces {
+            match result.last_mut() {
+                Some(last) if last.start == piece.start => *last = piece,
+                Some(last) if last.offset == piece.offset => {}
+                _ => result.push(piece),
+            }
+        }
+        Self { pieces: result }
+    }
 
-        // println!("input: {range_start}-{range_end}");
-        // println!("self: {self:?}");
+    fn index_of(&self, x: i64) -> usize {
+        self.pieces.partition_point(|piece| piece.start <= x) - 1
+    }
 
-        let (left_remnant, range_start) = if range_start < source_start {
-            (
-                Some(Range(range_start, std::cmp::min(range_end, source_start))),
-                source_start,
-            )
-        } else {
-            (None, range_start)
-        };
+    fn piece(&self, i: usize) -> (Range, i64) {
+        let end = self.pieces.get(i + 1).map_or(i64::MAX, |next| next.start);
+        (Range(self.pieces[i].start, end), self.pieces[i].offset)
+    }
 
-        let (right_remnant, range_end) = if range_end > source_end {
-            (
-                Some(Range(std::cmp::max(range_start, source_end), range_end)),
-                source_end,
-            )
-        } else {
-            (None, range_end)
-        };
+    fn ranges(&self) -> impl Iterator<Item = (Range, i64)> + '_ {
+        (0..self.pieces.len()).map(|i| self.piece(i))
+    }
 
-        let moved_center = if range_start < range_end {
-            Some(Range(
-                range_start - source_start + self.destination_start,
-                range_end - source_start + self.destination_start,
-            ))
-        } else {
-            None
-        };
+    pub fn map(&self, x: i64) -> i64 {
+        x + self.pieces[self.index_of(x)].offset
+    }
 
-        // println!("left remnant: {left_remnant:?}");
-        // println!("right remnant: {right_remnant:?}");
-        // println!("moved_center: {moved_center:?}");
+    /// The map that applies `self`, then `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        let mut pieces = vec![];
+        for (Range(a, b), d) in self.ranges() {
+            let (image_start, image_end) = (a + d, b + d);
+            let mut j = next.index_of(image_start);
+            loop {
+                let (Range(c, e), d2) = next.piece(j);
+                pieces.push(Piece {
+                    start: c.max(image_start) - d,
+                    offset: d + d2,
+                });
+                if e >= image_end {
+                    break;
+                }
+                j += 1;
+            }
+        }
+        Self::normalized(pieces)
+    }
 
-        // println!("result: {result:?}");
+    /// The map back from outputs to inputs. Fails unless every number is the output of
+    /// exactly one input.
+    pub fn inverse(&self) -> common::Result<Self> {
+        let mut images = self
+            .ranges()
+            .map(|(Range(a, b), d)| (Range(a + d, b + d), d))
+            .collect::<Vec<_>>();
+        images.sort_by_key(|(Range(a, _), _)| *a);
+        let mut expected_start = i64::MIN;
+        for (Range(a, b), _) in &images {
+            if *a != expected_start {
+                let what = if *a < expected_start {
+                    "overlap"
+                } else {
+                    "leave a gap"
+                };
+                return Err(common::Error::Invalid(format!(
+                    "map is not invertible, its outputs {what} at {}",
+                    a.min(&expected_start)
+                )));
+            }
+            expected_start = *b;
+        }
+        Ok(Self::normalized(
+            images
+                .into_iter()
+                .map(|(Range(a, _), d)| Piece {
+                    start: a,
+                    offset: -d,
+                })
+                .collect(),
+        ))
+    }
 
-        (moved_center, left_remnant, right_remnant)
+    /// The smallest output for an input in `range`, or `None` if `range` is empty.
+    pub fn min_over(&self, Range(start, end): Range) -> Option<i64> {
+        (start < end).then_some(())?;
+        (self.index_of(start)..self.pieces.len())
+            .map(|i| self.piece(i))
+            .take_while(|(Range(a, _), _)| *a < end)
+            .map(|(Range(a, _), d)| a.max(start) + d)
+            .min()
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+struct ShuffleOp {
+    source_start: i64,
+    source_end: i64,
+    destination_start: i64,
+    line: usize,
+}
+
 #[derive(Debug)]
 struct Map {
-    _source: String,
+    source: String,
     destination: String,
     shuffle_ops: Vec<ShuffleOp>,
 }
 
 impl Map {
-    fn map(&self, value: i64) -> i64 {
-        for shuffle_op in &self.shuffle_ops {
-            if let Some(mapped_value) = shuffle_op.map(value) {
-                return mapped_value;
+    /// The map as pieces; numbers outside every op map to themselves. Ops never overlap, as
+    /// `parse_input` rejects them.
+    fn piecewise(&self) -> PiecewiseMap {
+        let mut bounds = self
+            .shuffle_ops
+            .iter()
+            .flat_map(|op| [op.source_start, op.source_end])
+            .chain([i64::MIN])
+            .collect::<Vec<_>>();
+        bounds.sort();
+        bounds.dedup();
+        PiecewiseMap::normalized(
+            bounds
+                .into_iter()
+                .map(|start| Piece {
+                    start,
+                    offset: self
+                        .shuffle_ops
+                        .iter()
+                        .find(|op| op.source_start <= start && start < op.source_end)
+                        .map_or(0, |op| op.destination_start - op.source_start),
+                })
+                .collect(),
+        )
+    }
+}
+
+/// The maps of an almanac by source category, with the chains composed so far.
+#[derive(Debug, Default)]
+pub struct Almanac {
+    maps: HashMap<String, Map>,
+    composed: HashMap<(String, String), PiecewiseMap>,
+}
+
+impl Almanac {
+    /// Fails if following the `X-to-Y map:` links from some category comes back to it, or if
+    /// the links from `from` stop at a category with no map before reaching `to`.
+    pub fn validate(&self, from: &str, to: &str) -> common::Result<()> {
+        for start in self.maps.keys() {
+            let mut path = vec![start.as_str()];
+            let mut category = start.as_str();
+            while let Some(map) = self.maps.get(category) {
+                category = map.destination.as_str();
+                path.push(category);
+                if category == start {
+                    let message = format!("maps form a cycle: {}", path.join(" -> "));
+                    return Err(common::Error::Invalid(message));
+                }
+                if path.len() > self.maps.len() + 1 {
+                    break;
+                }
             }
         }
-        value
+        let mut category = from;
+        while category != to {
+            let Some(map) = self.maps.get(category) else {
+                let message = format!("missing link: no map from {category} on the way to {to}");
+                return Err(common::Error::Invalid(message));
+            };
+            category = map.destination.as_str();
+        }
+        Ok(())
     }
 
-    fn map_range(&self, range: Range) -> impl Iterator<Item = Range> {
-        let mut untouched = vec![range];
-        let mut touched = vec![];
-        for shuffle_op in &self.shuffle_ops {
-            untouched = untouched
-                .into_iter()
-                .flat_map(|r| {
-                    let (a, b, c) = shuffle_op.map_range(r);
-                    touched.extend(a);
-                    b.into_iter().chain(c)
-                })
-                .collect();
+    /// The categories from `from` to `to`, both included, following the map links.
+    pub fn route<'a>(&'a self, from: &'a str, to: &str) -> common::Result<Vec<&'a str>> {
+        let mut path = vec![from];
+        let mut category = from;
+        while category != to {
+            let map = self.maps.get(category).ok_or_else(|| {
+                common::Error::Invalid(format!(
+                    "no route from {from} to {to}: no map from {category}"
+                ))
+            })?;
+            if path.contains(&map.destination.as_str()) {
+                return Err(common::Error::Invalid(format!(
+                    "no route from {from} to {to}: maps form a cycle at {}",
+                    map.destination
+                )));
+            }
+            category = map.destination.as_str();
+            path.push(category);
         }
-        touched.into_iter().chain(untouched)
+        Ok(path)
     }
 
-    fn map_ranges(&self, ranges: Vec<Range>) -> Vec<Range> {
-        ranges.into_iter().flat_map(|r| self.map_range(r)).collect()
+    /// The single map from category `from` to `to`, composed along `route`.
+    pub fn chain(&mut self, from: &str, to: &str) -> common::Result<&PiecewiseMap> {
+        let key = (from.to_string(), to.to_string());
+        if !self.composed.contains_key(&key) {
+            let route = self.route(from, to)?;
+            let composed = route[..route.len() - 1]
+                .iter()
+                .fold(PiecewiseMap::identity(), |composed, category| {
+                    composed.then(&self.maps[*category].piecewise())
+                });
+            self.composed.insert(key.clone(), composed);
+        }
+        Ok(&self.composed[&key])
     }
 }
 
-fn parse_input<R: std::io::Read>(reader: R) -> (Vec<i64>, std::collections::HashMap<String, Map>) {
+fn parse_input<R: std::io::Read>(reader: R) -> common::Result<(Vec<i64>, Almanac)> {
     let reader = std::io::BufReader::new(reader);
 
     let mut seeds = vec![];
-    let mut maps = std::collections::HashMap::<String, Map>::new();
+    let mut maps = HashMap::<String, Map>::new();
     let mut current_map: Option<&mut Map> = None;
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = common::Line::new(i + 1, line.trim());
 
-    for line in reader.lines() {
-        let line = line.unwrap().trim().to_string();
-
-        if line.is_empty() {
+        if line.text.is_empty() {
             continue;
-        } else if line.starts_with("seeds:") {
+        } else if line.text.starts_with("seeds:") {
             seeds = line
-                .split_once(':')
-                .unwrap()
+                .split_once(line.text, ":")?
                 .1
                 .split_ascii_whitespace()
-                .map(|x| x.parse::<i64>().unwrap())
-                .collect();
-        } else if line.contains(" map:") {
-            let (source, destination) = line.split_once(' ').unwrap().0.split_once("-to-").unwrap();
+                .map(|x| line.parse::<i64>(x))
+                .collect::<Result<_, _>>()?;
+        } else if line.text.contains(" map:") {
+            let (name, _) = line.split_once(line.text, " ")?;
+            let (source, destination) = line.split_once(name, "-to-")?;
+            if let Some(map) = maps.get(source) {
+                let message = format!(
+                    "second map from {source}, already mapped to {}",
+                    map.destination
+                );
+                return Err(line.error(name, message).into());
+            }
             maps.insert(
                 source.into(),
                 Map {
-                    _source: source.into(),
+                    source: source.into(),
                     destination: destination.into(),
                     shuffle_ops: vec![],
                 },
             );
             current_map = maps.get_mut(source);
         } else {
-            let (destination_start, source_start, length) = {
-                let mut iter = line.split_ascii_whitespace();
-                (
-                    iter.next().unwrap().parse::<i64>().unwrap(),
-                    iter.next().unwrap().parse::<i64>().unwrap(),
-                    iter.next().unwrap().parse::<i64>().unwrap(),
-                )
+            let mut numbers = line.text.split_ascii_whitespace();
+            let mut number = |what| -> common::Result<i64> {
+                let text = line.require(numbers.next(), what)?;
+                let value = line.parse::<i64>(text)?;
+                if value < 0 {
+                    return Err(line.error(text, format!("negative {what}")).into());
+                }
+                Ok(value)
+            };
+            let destination_start = number("destination start")?;
+            let source_start = number("source start")?;
+            let length = number("range length")?;
+            let (Some(source_end), Some(_)) = (
+                source_start.checked_add(length),
+                destination_start.checked_add(length),
+            ) else {
+                let message = "range runs past the largest 64-bit number";
+                return Err(line.error(line.text, message).into());
             };
             let Some(ref mut v) = current_map else {
-                panic!("no current map");
+                return Err(line.error(line.text, "range outside of a map").into());
             };
-            v.shuffle_ops.push(ShuffleOp {
+            let op = ShuffleOp {
                 source_start,
-                source_end: source_start + length,
+                source_end,
                 destination_start,
-            });
+                line: line.number,
+            };
+            if let Some(other) = v.shuffle_ops.iter().find(|other| {
+                op.source_start < other.source_end && other.source_start < op.source_end
+            }) {
+                let message = format!(
+                    "{}-to-{} source range overlaps the one on line {}",
+                    v.source, v.destination, other.line
+                );
+                return Err(line.error(line.text, message).into());
+            }
+            v.shuffle_ops.push(op);
         }
     }
-    (seeds, maps)
+    let almanac = Almanac {
+        maps,
+        ..Default::default()
+    };
+    Ok((seeds, almanac))
 }
 
-pub fn part1<R: std::io::Read>(reader: R) -> i64 {
-    let (seeds, maps) = parse_input(reader);
-
-    let mut current_category = "seed";
-    let mut current_values = seeds.clone();
-    while current_category != "location" {
-        println!("current category: {current_category}");
-        println!("current values: {current_values:?}");
-        let map = &maps[current_category];
-        let next_category = map.destination.as_str();
-        let next_values = current_values
-            .iter()
-            .map(|&x| map.map(x))
-            .collect::<Vec<_>>();
-        current_category = next_category;
-        current_values = next_values;
-    }
+pub fn part1<R: std::io::Read>(reader: R) -> common::Result<i64> {
+    part1_route(reader, "seed", "location")
+}
 
-    println!("final locations: {current_values:?}");
+/// Part 1 with the seed numbers taken as `from` numbers, mapped to `to`.
+pub fn part1_route<R: std::io::Read>(reader: R, from: &str, to: &str) -> common::Result<i64> {
+    let (seeds, mut almanac) = parse_input(reader)?;
+    almanac.validate(from, to)?;
+    let map = almanac.chain(from, to)?;
+    seeds
+        .into_iter()
+        .map(|seed| map.map(seed))
+        .min()
+        .ok_or_else(|| common::Error::Invalid("no seeds".into()))
+}
 
-    *current_values.iter().min().unwrap()
+pub fn part2<R: std::io::Read>(reader: R) -> common::Result<i64> {
+    part2_route(reader, "seed", "location")
 }
 
-pub fn part2<R: std::io::Read>(reader: R) -> i64 {
-    let (seeds, maps) = parse_input(reader);
-    let mut current_ranges = seeds
+/// Part 2 with the seed ranges taken as `from` numbers, mapped to `to`.
+pub fn part2_route<R: std::io::Read>(reader: R, from: &str, to: &str) -> common::Result<i64> {
+    let (seeds, mut almanac) = parse_input(reader)?;
+    almanac.validate(from, to)?;
+    let map = almanac.chain(from, to)?;
+    if seeds.len() % 2 == 1 {
+        let message = format!("{} seed numbers do not pair up into ranges", seeds.len());
+        return Err(common::Error::Invalid(message));
+    }
+    seeds
         .into_iter()
         .array_chunks::<2>()
-        .map(|[a, b]| Range(a, a + b))
-        .collect::<Vec<_>>();
+        .map(|[a, b]| {
+            let end = a.checked_add(b).ok_or_else(|| {
+                common::Error::Invalid(format!("seed range {a} {b} runs past i64::MAX"))
+            })?;
+            Ok(map.min_over(Range(a, end)))
+        })
+        .collect::<common::Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .min()
+        .ok_or_else(|| common::Error::Invalid("no seeds".into()))
+}
+
+/// The categories the seed numbers belong to and are mapped to; seed and location unless set.
+#[derive(Debug, Clone, Default)]
+pub struct Route {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
 
-    let mut current_category = "seed";
-    while current_category != "location" {
-        println!("current category: {current_category}");
-        println!("current values: {current_ranges:?}");
-        let map = &maps[current_category];
-        current_category = map.destination.as_str();
-        current_ranges = map.map_ranges(current_ranges);
+impl Route {
+    fn from(&self) -> &str {
+        self.from.as_deref().unwrap_or("seed")
     }
 
-    println!("final locations: {current_ranges:?}");
+    fn to(&self) -> &str {
+        self.to.as_deref().unwrap_or("location")
+    }
+}
 
-    current_ranges
-        .into_iter()
-        .flat_map(|Range(a, b)| if a < b { Some(a) } else { None })
-        .min()
-        .unwrap()
+impl common::Params for Route {
+    fn set(&mut self, name: &str, value: &str) -> common::Result<()> {
+        match name {
+            "from" => self.from = Some(value.trim().into()),
+            "to" => self.to = Some(value.trim().into()),
+            _ => return Err(common::Error::unknown_param(name)),
+        }
+        Ok(())
+    }
+}
+
+pub struct Day05;
+
+impl common::Solver for Day05 {
+    type Params1 = Route;
+    type Params2 = Route;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn part1(input: &str, params: &Route) -> common::Result<i64> {
+        part1_route(input.as_bytes(), params.from(), params.to())
+    }
+
+    fn part2(input: &str, params: &Route) -> common::Result<i64> {
+        part2_route(input.as_bytes(), params.from(), params.to())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     #[test]
-    fn test_part1_example() {
+    fn test_part1_example() -> common::Result<()> {
         let input = include_str!("example1.txt");
-        let answer = super::part1(input.as_bytes());
+        let answer = super::part1(input.as_bytes())?;
         assert_eq!(answer, 35);
+        Ok(())
     }
     #[test]
-    fn test_part1_final() {
+    fn test_part1_final() -> common::Result<()> {
         let input = include_str!("big.txt");
-        let answer = super::part1(input.as_bytes());
+        let answer = super::part1(input.as_bytes())?;
         assert_eq!(answer, 825516882);
+        Ok(())
+    }
+    #[test]
+    fn test_malformed() {
+        let input = "seeds: 79 14\n\nseed-to-soil map:\n50 98\n";
+        let Err(common::Error::Parse(err)) = super::part1(input.as_bytes()) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(
+            (err.line, err.column, err.message.as_str()),
+            (4, 6, "missing range length")
+        );
+
+        let input = "seeds: 1\n\na-to-b map:\n0 9223372036854775800 100\n";
+        let Err(common::Error::Parse(err)) = super::part1(input.as_bytes()) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(err.line, 4);
+        assert!(err.message.contains("largest"), "{}", err.message);
+        let input = "seeds: 1\n\na-to-b map:\n9223372036854775800 0 100\n";
+        let Err(common::Error::Parse(err)) = super::part1(input.as_bytes()) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(err.line, 4);
+        let input = "seeds: 1\n\na-to-b map:\n0 -5 10\n";
+        let Err(common::Error::Parse(err)) = super::part1(input.as_bytes()) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!((err.line, err.column, err.text.as_str()), (4, 3, "-5"));
+
+        let input = "seeds: 9223372036854775800 100\n";
+        let Err(common::Error::Invalid(message)) =
+            super::part2_route(input.as_bytes(), "seed", "seed")
+        else {
+            panic!("expected an invalid seed range");
+        };
+        assert!(message.contains("runs past"), "{message}");
+
+        let input = "seeds: 1 2 3\n";
+        let Err(common::Error::Invalid(message)) =
+            super::part2_route(input.as_bytes(), "seed", "seed")
+        else {
+            panic!("expected an odd number of seeds");
+        };
+        assert_eq!(message, "3 seed numbers do not pair up into ranges");
     }
+
+    #[test]
+    fn test_compose_and_invert() -> common::Result<()> {
+        let (_, mut almanac) = super::parse_input(include_str!("example1.txt").as_bytes())?;
+        let seed_to_soil = almanac.chain("seed", "soil")?.clone();
+        let soil_to_location = almanac.chain("soil", "location")?.clone();
+        let seed_to_location = almanac.chain("seed", "location")?.clone();
+        assert_eq!(seed_to_soil.then(&soil_to_location), seed_to_location);
+        assert_eq!(
+            [79, 14, 55, 13].map(|x| seed_to_location.map(x)),
+            [82, 43, 86, 35]
+        );
+        let location_to_seed = seed_to_location.inverse()?;
+        assert_eq!(location_to_seed.map(82), 79);
+        assert_eq!(
+            location_to_seed.then(&seed_to_location),
+            super::PiecewiseMap::identity()
+        );
+        assert_eq!(seed_to_location.min_over(super::Range(79, 93)), Some(46));
+        assert_eq!(seed_to_location.min_over(super::Range(5, 5)), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_routes_and_validation() -> common::Result<()> {
+        let (_, almanac) = super::parse_input(include_str!("example1.txt").as_bytes())?;
+        assert_eq!(
+            almanac.route("light", "humidity")?,
+            ["light", "temperature", "humidity"]
+        );
+        assert!(almanac.route("location", "seed").is_err());
+        let input = include_str!("example1.txt").as_bytes();
+        assert_eq!(super::part1_route(input, "soil", "fertilizer")?, 52);
+
+        let overlapping = "seeds: 1\n\na-to-b map:\n0 10 5\n0 20 5\n0 12 1\n";
+        let Err(common::Error::Parse(err)) = super::parse_input(overlapping.as_bytes()) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(err.line, 6);
+        assert!(err.message.ends_with("line 4"), "{}", err.message);
+
+        let cycle = "seeds: 1\n\na-to-b map:\n0 10 5\n\nb-to-a map:\n0 10 5\n";
+        let (_, almanac) = super::parse_input(cycle.as_bytes())?;
+        let Err(common::Error::Invalid(message)) = almanac.validate("a", "b") else {
+            panic!("expected a validation error");
+        };
+        assert!(message.starts_with("maps form a cycle"), "{message}");
+
+        let gap = "seeds: 1\n\na-to-b map:\n0 10 5\n\nc-to-d map:\n0 10 5\n";
+        let (_, almanac) = super::parse_input(gap.as_bytes())?;
+        almanac.validate("c", "d")?;
+        let Err(common::Error::Invalid(message)) = almanac.validate("a", "d") else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(message, "missing link: no map from b on the way to d");
+        Ok(())
+    }
+
     #[test]
-    fn test_part2_example() {
+    fn test_part2_example() -> common::Result<()> {
         let input = include_str!("example1.txt");
-        let answer = super::part2(input.as_bytes());
+        let answer = super::part2(input.as_bytes())?;
         assert_eq!(answer, 46);
+        Ok(())
     }
     #[test]
-    fn test_part2_final() {
+    fn test_part2_final() -> common::Result<()> {
         let input = include_str!("big.txt");
-        let answer = super::part2(input.as_bytes());
+        let answer = super::part2(input.as_bytes())?;
         assert_eq!(answer, 136096660);
+        Ok(())
     }
 }
@@ -1,81 +1,152 @@
 use std::io::BufRead;
 
-pub fn part1<R: std::io::Read>(reader: R) -> i64 {
-    let reader = std::io::BufReader::new(reader);
-    let mut line_iter = reader.lines().map(|x| x.unwrap());
-    let time_line = line_iter.next().unwrap();
-    let distance_line = line_iter.next().unwrap();
-    let races = time_line
-        .split_ascii_whitespace()
-        .zip(distance_line.split_ascii_whitespace())
-        .skip(1)
-        .map(|(t, d)| (t.parse::<i64>().unwrap(), d.parse::<i64>().unwrap()))
-        .collect::<Vec<_>>();
-    let mut winner_counts = vec![];
-    for (total_time, record_distance) in races {
-        let mut winner_count = 0;
-        for t in 1..total_time {
-            let d = (total_time - t) * t;
-            if d > record_distance {
-                winner_count += 1;
-            }
+/// Reads the "Time:" and "Distance:" lines and hands each one's values to `parse_values`.
+fn parse_input<R, T>(
+    reader: R,
+    parse_values: impl Fn(&common::Line, &str) -> common::Result<T>,
+) -> common::Result<(T, T)>
+where
+    R: std::io::Read,
+{
+    let mut line_iter = std::io::BufReader::new(reader).lines();
+    let mut next_values = |number: usize, label: &str| -> common::Result<T> {
+        let text = line_iter.next().transpose()?.unwrap_or_default();
+        let line = common::Line::new(number, &text);
+        let (name, values) = line.split_once(line.text, ":")?;
+        if name.trim() != label {
+            return Err(line.error(name, format!("expected {label:?}")).into());
         }
-        winner_counts.push(winner_count);
+        parse_values(&line, values)
+    };
+    let times = next_values(1, "Time")?;
+    let distances = next_values(2, "Distance")?;
+    Ok((times, distances))
+}
+
+/// How many whole hold times beat `record` in a race of `time`. Holding for `t` goes
+/// `t * (time - t)`, which beats the record exactly when `|2t - time|` is below
+/// `sqrt(time² - 4 record)`; counting the integers of `time`'s parity in that window needs
+/// one integer square root and no floating point. A negative `time` has no hold times.
+pub fn ways_to_win<N>(time: &N, record: &N) -> N
+where
+    N: num::Integer + num::integer::Roots + Clone,
+{
+    if *time < N::zero() {
+        return N::zero();
+    }
+    let two = N::one() + N::one();
+    let discriminant = time.clone() * time.clone() - two.clone() * two * record.clone();
+    if discriminant <= N::zero() {
+        return N::zero();
+    }
+    // The widest |2t - time| whose square is still below the discriminant.
+    let reach = (discriminant - N::one()).sqrt().min(time.clone());
+    if reach.is_odd() == time.is_odd() {
+        reach + N::one()
+    } else {
+        reach
     }
-    winner_counts.into_iter().product()
 }
 
-pub fn part2<R: std::io::Read>(reader: R) -> i64 {
-    let reader = std::io::BufReader::new(reader);
-    let mut line_iter = reader.lines().map(|x| x.unwrap());
-    let time_line = line_iter.next().unwrap();
-    let distance_line = line_iter.next().unwrap();
-    let parse_line = |line: String| {
-        line.split_ascii_whitespace()
-            .skip(1)
-            .collect::<String>()
-            .parse::<i64>()
-            .unwrap()
-    };
-    let total_time = parse_line(time_line);
-    let record_distance = parse_line(distance_line);
-    let mut winner_count = 0;
-    for t in 1..total_time {
-        let d = (total_time - t) * t;
-        if d > record_distance {
-            winner_count += 1;
-        }
+pub fn part1<R: std::io::Read>(reader: R) -> common::Result<i64> {
+    let (times, distances) = parse_input(reader, |line, values| {
+        Ok(values
+            .split_ascii_whitespace()
+            .map(|v| line.parse::<i64>(v))
+            .collect::<Result<Vec<_>, _>>()?)
+    })?;
+    if times.len() != distances.len() {
+        return Err(common::Error::Invalid(format!(
+            "{} times but {} distances",
+            times.len(),
+            distances.len()
+        )));
+    }
+    times
+        .into_iter()
+        .zip(distances)
+        .map(|(time, record)| {
+            let count = ways_to_win(&i128::from(time), &i128::from(record));
+            i64::try_from(count)
+                .map_err(|_| common::Error::Invalid(format!("{count} ways to win overflow i64")))
+        })
+        .product()
+}
+
+/// Reads each line's digits as one number, however many there are.
+pub fn part2<R: std::io::Read>(reader: R) -> common::Result<num::BigInt> {
+    let (time, record) = parse_input(reader, |line, values| {
+        let kerned = values.split_ascii_whitespace().collect::<String>();
+        Ok(kerned
+            .parse::<num::BigInt>()
+            .map_err(|e| line.error(values.trim(), format!("cannot parse BigInt ({e})")))?)
+    })?;
+    Ok(ways_to_win(&time, &record))
+}
+
+pub struct Day06;
+
+impl common::Solver for Day06 {
+    type Params1 = ();
+    type Params2 = ();
+    type Answer1 = i64;
+    type Answer2 = num::BigInt;
+
+    fn part1(input: &str, _: &()) -> common::Result<i64> {
+        part1(input.as_bytes())
+    }
+
+    fn part2(input: &str, _: &()) -> common::Result<num::BigInt> {
+        part2(input.as_bytes())
     }
-    winner_count
 }
 
 #[cfg(test)]
 mod tests {
     #[test]
-    fn test_part1_example() {
+    fn test_part1_example() -> common::Result<()> {
         let input = include_str!("example1.txt");
-        let answer = super::part1(input.as_bytes());
+        let answer = super::part1(input.as_bytes())?;
         assert_eq!(answer, 288);
+        Ok(())
     }
 
     #[test]
-    fn test_part1_final() {
+    fn test_part1_final() -> common::Result<()> {
         let input = include_str!("final.txt");
-        let answer = super::part1(input.as_bytes());
+        let answer = super::part1(input.as_bytes())?;
         assert_eq!(answer, 1155175);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ways_to_win() {
+        for time in -5i64..40 {
+            for record in -2..=time * time / 4 + 1 {
+                let brute_force = (0..=time).filter(|t| t * (time - t) > record).count();
+                assert_eq!(super::ways_to_win(&time, &record), brute_force as i64);
+            }
+        }
+        let time = num::BigInt::from(10).pow(40);
+        let half: num::BigInt = &time / 2;
+        let record = &half * &half - 4;
+        assert_eq!(super::ways_to_win(&time, &record), 3.into());
+        assert_eq!(super::ways_to_win(&time, &(record + 4)), 0.into());
     }
 
     #[test]
-    fn test_part2_example() {
+    fn test_part2_example() -> common::Result<()> {
         let input = include_str!("example1.txt");
-        let answer = super::part2(input.as_bytes());
-        assert_eq!(answer, 71503);
+        let answer = super::part2(input.as_bytes())?;
+        assert_eq!(answer, 71503.into());
+        Ok(())
     }
 
     #[test]
-    fn test_part2_final() {
+    fn test_part2_final() -> common::Result<()> {
         let input = include_str!("final.txt");
-        let answer = super::part2(input.as_bytes());
-        assert_eq!(answer, 35961505);
+        let answer = super::part2(input.as_bytes())?;
+        assert_eq!(answer, 35961505.into());
+        Ok(())
     }
 }
@@ -1,7 +1,7 @@
-use std::{io::BufRead, str::FromStr};
+use std::io::BufRead;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-enum HandType {
+pub enum HandType {
     HighCard,
     OnePair,
     TwoPairs,
@@ -12,15 +12,9 @@ enum HandType {
 }
 
 impl HandType {
-    fn from_cards(cards: &[Card]) -> Self {
-        let mut counts = std::collections::HashMap::<Card, u64>::new();
-        for card in cards {
-            *counts.entry(*card).or_default() += 1;
-        }
-        let mut counts = counts.into_values().filter(|&x| x > 0).collect::<Vec<_>>();
-        counts.sort();
-        counts.reverse();
-        match counts.as_slice() {
+    /// The type of a hand whose cards come in groups of `counts`, largest first.
+    fn from_counts(counts: &[usize]) -> Self {
+        match counts {
             [5] => HandType::FiveOfAKind,
             [4, 1] => HandType::FourOfAKind,
             [3, 2] => HandType::FullHouse,
@@ -28,224 +22,397 @@ impl HandType {
             [2, 2, 1] => HandType::TwoPairs,
             [2, 1, 1, 1] => HandType::OnePair,
             [1, 1, 1, 1, 1] => HandType::HighCard,
-            _ => panic!("invalid hand: {:?}", cards),
+            _ => panic!("invalid hand: {counts:?}"),
         }
     }
+}
 
-    fn from_cards2(cards: &[Card2]) -> Self {
-        let mut counts = std::collections::HashMap::<Card2, u64>::new();
-        let mut joker_count = 0;
-        for card in cards {
-            match card {
-                Card2::J => joker_count += 1,
-                _ => *counts.entry(*card).or_default() += 1,
-            }
+/// How cards rank against each other and which of them are wild. A wild card joins
+/// whichever group of cards makes the best hand type, but still ranks by `order` in
+/// tie-breaks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    order: Vec<char>,
+    wild: Vec<char>,
+}
+
+impl Rules {
+    /// `order` lists every card label from weakest to strongest; `wild` lists the wild ones.
+    pub fn new(order: &str, wild: &str) -> common::Result<Self> {
+        let order = order.chars().collect::<Vec<_>>();
+        let wild = wild.chars().collect::<Vec<_>>();
+        if let Some((i, c)) = order
+            .iter()
+            .enumerate()
+            .find(|(i, c)| order[..*i].contains(c))
+        {
+            let message = format!("card {c:?} appears twice in order, at {i}");
+            return Err(common::Error::Invalid(message));
+        }
+        if let Some(c) = wild.iter().find(|c| !order.contains(c)) {
+            return Err(common::Error::Invalid(format!(
+                "wild card {c:?} is not in order"
+            )));
         }
-        if joker_count == 5 {
-            return HandType::FiveOfAKind;
+        Ok(Self { order, wild })
+    }
+
+    /// Part 1: `23456789TJQKA`, nothing wild.
+    pub fn standard() -> Self {
+        Self::new("23456789TJQKA", "").unwrap()
+    }
+
+    /// Part 2: `J` is wild and the weakest card.
+    pub fn jokers_wild() -> Self {
+        Self::new("J23456789TQKA", "J").unwrap()
+    }
+
+    pub fn strength(&self, card: char) -> Option<usize> {
+        self.order.iter().position(|&c| c == card)
+    }
+
+    pub fn is_wild(&self, card: char) -> bool {
+        self.wild.contains(&card)
+    }
+
+    pub fn hand_type(&self, cards: &[char; 5]) -> HandType {
+        self.classify(cards).0
+    }
+
+    /// The hand type of `cards`, and the card the wild ones stand in for if there are any:
+    /// the most common other card, or the strongest card that is not wild if all five are.
+    pub fn classify(&self, cards: &[char; 5]) -> (HandType, Option<char>) {
+        let mut counts = std::collections::HashMap::<char, usize>::new();
+        let mut wild_count = 0;
+        for &card in cards {
+            if self.is_wild(card) {
+                wild_count += 1;
+            } else {
+                *counts.entry(card).or_default() += 1;
+            }
         }
-        let mut counts = counts.into_values().filter(|&x| x > 0).collect::<Vec<_>>();
+        let substitution = (wild_count > 0).then(|| {
+            counts
+                .iter()
+                .max_by_key(|&(&card, &count)| (count, self.strength(card)))
+                .map(|(&card, _)| card)
+                .or_else(|| {
+                    self.order
+                        .iter()
+                        .rev()
+                        .find(|&&c| !self.is_wild(c))
+                        .copied()
+                })
+                .unwrap_or(cards[0])
+        });
+        let mut counts = counts.into_values().collect::<Vec<_>>();
         counts.sort();
         counts.reverse();
-        counts[0] += joker_count;
-        match counts.as_slice() {
-            [5] => HandType::FiveOfAKind,
-            [4, 1] => HandType::FourOfAKind,
-            [3, 2] => HandType::FullHouse,
-            [3, 1, 1] => HandType::ThreeOfAKind,
-            [2, 2, 1] => HandType::TwoPairs,
-            [2, 1, 1, 1] => HandType::OnePair,
-            [1, 1, 1, 1, 1] => HandType::HighCard,
-            _ => panic!("invalid hand: {:?}", cards),
+        match counts.first_mut() {
+            Some(largest) => *largest += wild_count,
+            None => counts.push(wild_count),
         }
+        (HandType::from_counts(&counts), substitution)
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-enum Card {
-    _2,
-    _3,
-    _4,
-    _5,
-    _6,
-    _7,
-    _8,
-    _9,
-    T,
-    J,
-    Q,
-    K,
-    A,
-}
-
-impl TryFrom<char> for Card {
-    type Error = String;
-
-    fn try_from(c: char) -> Result<Self, Self::Error> {
-        match c {
-            '2' => Ok(Card::_2),
-            '3' => Ok(Card::_3),
-            '4' => Ok(Card::_4),
-            '5' => Ok(Card::_5),
-            '6' => Ok(Card::_6),
-            '7' => Ok(Card::_7),
-            '8' => Ok(Card::_8),
-            '9' => Ok(Card::_9),
-            'T' => Ok(Card::T),
-            'J' => Ok(Card::J),
-            'Q' => Ok(Card::Q),
-            'K' => Ok(Card::K),
-            'A' => Ok(Card::A),
-            _ => Err(format!("invalid card: {}", c)),
-        }
-    }
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Hand {
+    pub type_: HandType,
+    strengths: [usize; 5],
+    pub cards: [char; 5],
+    pub bid: i64,
+    /// What the wild cards in the hand count as for its type.
+    pub substitution: Option<char>,
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-struct Hand {
-    type_: HandType,
-    cards: [Card; 5],
-    bid: i64,
+/// What decided a comparison between two hands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    Type(HandType, HandType),
+    /// The first position, counting from 0, where the cards differ.
+    Card {
+        position: usize,
+        ours: char,
+        theirs: char,
+    },
+    Identical,
 }
 
-impl FromStr for Hand {
-    type Err = String;
+impl std::fmt::Display for TieBreak {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Type(ours, theirs) => write!(f, "{ours:?} vs {theirs:?}"),
+            Self::Card {
+                position,
+                ours,
+                theirs,
+            } => write!(f, "same type, card {} is {ours} vs {theirs}", position + 1),
+            Self::Identical => write!(f, "identical cards"),
+        }
+    }
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut iter = s.split_ascii_whitespace();
-        let cards: [Card; 5] = iter
-            .next()
-            .unwrap()
+impl Hand {
+    pub fn parse(line: &common::Line, rules: &Rules) -> Result<Self, common::ParseError> {
+        let mut iter = line.text.split_ascii_whitespace();
+        let cards_str = line.require(iter.next(), "cards")?;
+        let cards = cards_str
             .chars()
-            .map(|c| Card::try_from(c).unwrap())
             .collect::<Vec<_>>()
             .try_into()
-            .unwrap();
-        let bid = iter.next().unwrap().parse::<i64>().unwrap();
-        Ok(Hand {
-            type_: HandType::from_cards(&cards),
+            .map_err(|_| line.error(cards_str, "expected 5 cards"))?;
+        let bid = line.parse::<i64>(line.require(iter.next(), "bid")?)?;
+        Self::new(cards, bid, rules).map_err(|i| {
+            let at = cards_str.char_indices().nth(i).unwrap().0;
+            line.error(&cards_str[at..at + cards[i].len_utf8()], "unknown card")
+        })
+    }
+
+    /// Fails with the index of the first card `rules` does not know.
+    pub fn new(cards: [char; 5], bid: i64, rules: &Rules) -> Result<Self, usize> {
+        let mut strengths = [0; 5];
+        for (i, &card) in cards.iter().enumerate() {
+            strengths[i] = rules.strength(card).ok_or(i)?;
+        }
+        let (type_, substitution) = rules.classify(&cards);
+        Ok(Self {
+            type_,
+            strengths,
             cards,
             bid,
+            substitution,
         })
     }
-}
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-struct Hand2 {
-    type_: HandType,
-    cards: [Card2; 5],
-    bid: i64,
-}
+    /// The cards as they count for the hand type, with the wild ones substituted.
+    pub fn effective_cards(&self, rules: &Rules) -> [char; 5] {
+        self.cards.map(|card| match self.substitution {
+            Some(substitution) if rules.is_wild(card) => substitution,
+            _ => card,
+        })
+    }
 
-impl From<Hand> for Hand2 {
-    fn from(value: Hand) -> Self {
-        let cards: [Card2; 5] = value
-            .cards
-            .iter()
-            .map(|&x| x.into())
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap();
-        Hand2 {
-            type_: HandType::from_cards2(&cards),
-            cards,
-            bid: value.bid,
+    /// How this hand orders against `other`, and why. Both must come from the same rules.
+    pub fn compare(&self, other: &Self) -> (std::cmp::Ordering, TieBreak) {
+        if self.type_ != other.type_ {
+            let tie_break = TieBreak::Type(self.type_, other.type_);
+            return (self.type_.cmp(&other.type_), tie_break);
+        }
+        match (0..5).find(|&i| self.strengths[i] != other.strengths[i]) {
+            Some(position) => (
+                self.strengths[position].cmp(&other.strengths[position]),
+                TieBreak::Card {
+                    position,
+                    ours: self.cards[position],
+                    theirs: other.cards[position],
+                },
+            ),
+            None => (std::cmp::Ordering::Equal, TieBreak::Identical),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-enum Card2 {
-    J,
-    _2,
-    _3,
-    _4,
-    _5,
-    _6,
-    _7,
-    _8,
-    _9,
-    T,
-    Q,
-    K,
-    A,
-}
-
-impl From<Card> for Card2 {
-    fn from(value: Card) -> Self {
-        match value {
-            Card::J => Card2::J,
-            Card::_2 => Card2::_2,
-            Card::_3 => Card2::_3,
-            Card::_4 => Card2::_4,
-            Card::_5 => Card2::_5,
-            Card::_6 => Card2::_6,
-            Card::_7 => Card2::_7,
-            Card::_8 => Card2::_8,
-            Card::_9 => Card2::_9,
-            Card::T => Card2::T,
-            Card::Q => Card2::Q,
-            Card::K => Card2::K,
-            Card::A => Card2::A,
+impl std::fmt::Display for Hand {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let cards = self.cards.iter().collect::<String>();
+        write!(f, "{cards} {:?}", self.type_)?;
+        if let Some(substitution) = self.substitution {
+            write!(f, " (wild as {substitution})")?;
         }
+        Ok(())
     }
 }
 
-pub fn part1<R: std::io::Read>(reader: R) -> i64 {
-    let reader = std::io::BufReader::new(reader);
-    let mut hands = reader
-        .lines()
-        .map(|x| x.unwrap().parse::<Hand>().unwrap())
-        .collect::<Vec<_>>();
+/// A hand's place in the ranking, the weakest hand ranking 1.
+#[derive(Debug, Clone)]
+pub struct Ranked {
+    pub rank: i64,
+    pub hand: Hand,
+}
+
+impl Ranked {
+    pub fn winnings(&self) -> i64 {
+        self.rank * self.hand.bid
+    }
+}
+
+impl std::fmt::Display for Ranked {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{:>5} {:<36} {} x {} = {}",
+            self.rank,
+            self.hand.to_string(),
+            self.hand.bid,
+            self.rank,
+            self.winnings()
+        )
+    }
+}
+
+pub fn rank(mut hands: Vec<Hand>) -> Vec<Ranked> {
     hands.sort();
     hands
         .into_iter()
         .enumerate()
-        .map(|(i, h)| (i64::try_from(i).unwrap() + 1) * h.bid)
-        .sum()
+        .map(|(i, hand)| Ranked {
+            rank: i64::try_from(i).unwrap() + 1,
+            hand,
+        })
+        .collect()
 }
 
-pub fn part2<R: std::io::Read>(reader: R) -> i64 {
+pub fn read_hands<R: std::io::Read>(reader: R, rules: &Rules) -> common::Result<Vec<Hand>> {
     let reader = std::io::BufReader::new(reader);
-    let mut hands: Vec<Hand2> = reader
-        .lines()
-        .map(|x| x.unwrap().parse::<Hand>().unwrap().into())
-        .collect::<Vec<_>>();
-    hands.sort();
-    hands
-        .into_iter()
-        .enumerate()
-        .map(|(i, h)| (i64::try_from(i).unwrap() + 1) * h.bid)
-        .sum()
+    let mut hands = vec![];
+    for (i, text) in reader.lines().enumerate() {
+        let text = text?;
+        hands.push(Hand::parse(&common::Line::new(i + 1, &text), rules)?);
+    }
+    Ok(hands)
+}
+
+/// The sum of each hand's bid times its rank, the weakest hand ranking 1.
+pub fn total_winnings(hands: Vec<Hand>) -> i64 {
+    rank(hands).iter().map(Ranked::winnings).sum()
+}
+
+pub fn part1<R: std::io::Read>(reader: R) -> common::Result<i64> {
+    Ok(total_winnings(read_hands(reader, &Rules::standard())?))
+}
+
+pub fn part2<R: std::io::Read>(reader: R) -> common::Result<i64> {
+    Ok(total_winnings(read_hands(reader, &Rules::jokers_wild())?))
+}
+
+/// Overrides for the card order (weakest first) and the wild cards; each part keeps its
+/// own default for whichever is not set.
+#[derive(Debug, Clone, Default)]
+pub struct RulesParam {
+    pub order: Option<String>,
+    pub wild: Option<String>,
+}
+
+impl RulesParam {
+    fn rules(&self, default: Rules) -> common::Result<Rules> {
+        let order = self.order.clone().unwrap_or(default.order.iter().collect());
+        let wild = self.wild.clone().unwrap_or(default.wild.iter().collect());
+        Rules::new(&order, &wild)
+    }
+}
+
+impl common::Params for RulesParam {
+    fn set(&mut self, name: &str, value: &str) -> common::Result<()> {
+        match name {
+            "order" => self.order = Some(value.trim().into()),
+            "wild" => self.wild = Some(value.trim().into()),
+            _ => return Err(common::Error::unknown_param(name)),
+        }
+        Ok(())
+    }
+}
+
+pub struct Day07;
+
+impl common::Solver for Day07 {
+    type Params1 = RulesParam;
+    type Params2 = RulesParam;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn part1(input: &str, params: &RulesParam) -> common::Result<i64> {
+        let rules = params.rules(Rules::standard())?;
+        Ok(total_winnings(read_hands(input.as_bytes(), &rules)?))
+    }
+
+    fn part2(input: &str, params: &RulesParam) -> common::Result<i64> {
+        let rules = params.rules(Rules::jokers_wild())?;
+        Ok(total_winnings(read_hands(input.as_bytes(), &rules)?))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     #[test]
-    fn test_part1_example() {
+    fn test_part1_example() -> common::Result<()> {
         let input = include_str!("example1.txt");
-        let answer = super::part1(input.as_bytes());
+        let answer = super::part1(input.as_bytes())?;
         assert_eq!(answer, 6440);
+        Ok(())
     }
 
     #[test]
-    fn test_part1_final() {
+    fn test_part1_final() -> common::Result<()> {
         let input = include_str!("final.txt");
-        let answer = super::part1(input.as_bytes());
+        let answer = super::part1(input.as_bytes())?;
         assert_eq!(answer, 250898830);
+        Ok(())
     }
 
     #[test]
-    fn test_part2_example() {
+    fn test_part2_example() -> common::Result<()> {
         let input = include_str!("example1.txt");
-        let answer = super::part2(input.as_bytes());
+        let answer = super::part2(input.as_bytes())?;
         assert_eq!(answer, 5905);
+        Ok(())
     }
 
     #[test]
-    fn test_part2_final() {
+    fn test_part2_final() -> common::Result<()> {
         let input = include_str!("final.txt");
-        let answer = super::part2(input.as_bytes());
+        let answer = super::part2(input.as_bytes())?;
         assert_eq!(answer, 252127335);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rules() -> common::Result<()> {
+        use super::HandType::*;
+        let wild = super::Rules::jokers_wild();
+        let types = ["JJJJJ", "JJJJ2", "JJ2J3", "J2233", "J2345"]
+            .map(|cards| wild.hand_type(&cards.chars().collect::<Vec<_>>().try_into().unwrap()));
+        assert_eq!(
+            types,
+            [FiveOfAKind, FiveOfAKind, FourOfAKind, FullHouse, OnePair]
+        );
+
+        // Aces low, and both 2 and 3 wild.
+        let rules = super::Rules::new("A23456789TJQK", "23")?;
+        let hands = super::read_hands("23KKA 1\nKKKA2 10\nAAAAQ 100\n".as_bytes(), &rules)?;
+        assert_eq!(hands[0].type_, FourOfAKind);
+        // All three are four of a kind, so AAAAQ, 23KKA and KKKA2 rank 1, 2 and 3.
+        assert_eq!(super::total_winnings(hands), 100 + 2 + 30);
+        assert!(super::Rules::new("AA", "").is_err());
+        assert!(super::Rules::new("A2", "J").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain() -> common::Result<()> {
+        let rules = super::Rules::jokers_wild();
+        let input = include_str!("example1.txt").as_bytes();
+        let ranked = super::rank(super::read_hands(input, &rules)?);
+        let listing = ranked.iter().map(|r| r.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            listing[4],
+            "    5 KTJJT FourOfAKind (wild as T)        220 x 5 = 1100"
+        );
+        let (best, second) = (&ranked[4].hand, &ranked[3].hand);
+        assert_eq!(best.effective_cards(&rules), ['K', 'T', 'T', 'T', 'T']);
+        let (ordering, tie_break) = best.compare(second);
+        assert_eq!(ordering, std::cmp::Ordering::Greater);
+        assert_eq!(tie_break.to_string(), "same type, card 1 is K vs Q");
+        let (_, tie_break) = ranked[0].hand.compare(best);
+        assert_eq!(tie_break.to_string(), "OnePair vs FourOfAKind");
+        Ok(())
+    }
+
+    #[test]
+    fn test_part1_malformed() {
+        let Err(common::Error::Parse(err)) = super::part1("32T3K 765\nT5X5J 684".as_bytes()) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 3, "X"));
     }
 }
@@ -1,71 +1,342 @@
 use num::Integer;
 
-pub fn part1<R: std::io::BufRead>(reader: R) -> i64 {
-    let mut lines = reader.lines();
-    let instructions = lines.next().unwrap().unwrap();
-    lines.next().unwrap().unwrap();
-
-    let mut map = std::collections::HashMap::<String, (String, String)>::new();
-    for line in lines {
-        let line = line.unwrap();
-        let (from, rest) = line.split_once(' ').unwrap();
-        let (_, rest) = rest.split_once('(').unwrap();
-        let (left, rest) = rest.split_once(',').unwrap();
-        let (right, _) = rest.trim().split_once(')').unwrap();
-        map.insert(from.to_string(), (left.to_string(), right.to_string()));
-    }
-
-    let mut current_node = "AAA";
+const NONE: u32 = u32::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Turn {
+    Left,
+    Right,
+}
+
+/// The L/R network with nodes numbered from 0 in the order they are defined.
+#[derive(Debug, Clone, Default)]
+pub struct Network {
+    names: Vec<String>,
+    ids: std::collections::HashMap<String, u32>,
+    edges: Vec<(u32, u32)>,
+}
+
+impl Network {
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn id(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: u32) -> &str {
+        &self.names[id as usize]
+    }
+
+    /// The left and right successors of `id`.
+    pub fn edges(&self, id: u32) -> (u32, u32) {
+        self.edges[id as usize]
+    }
+
+    pub fn step(&self, id: u32, turn: Turn) -> u32 {
+        let (left, right) = self.edges[id as usize];
+        match turn {
+            Turn::Left => left,
+            Turn::Right => right,
+        }
+    }
+
+    pub fn is_start(&self, id: u32) -> bool {
+        self.name(id).ends_with('A')
+    }
+
+    pub fn is_end(&self, id: u32) -> bool {
+        self.name(id).ends_with('Z')
+    }
+
+    /// The nodes whose names end with A, in order of definition.
+    pub fn starts(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..self.len() as u32).filter(|&id| self.is_start(id))
+    }
+}
+
+impl Network {
+    /// Strongly connected components over both turns, each sorted, in order of their first
+    /// node.
+    pub fn sccs(&self) -> Vec<Vec<u32>> {
+        // Tarjan's algorithm with an explicit stack of (node, edges tried).
+        let mut index = vec![NONE; self.len()];
+        let mut low = vec![0; self.len()];
+        let mut on_stack = vec![false; self.len()];
+        let mut stack = vec![];
+        let mut components = vec![];
+        let mut next_index = 0;
+        for root in 0..self.len() as u32 {
+            if index[root as usize] != NONE {
+                continue;
+            }
+            let mut calls = vec![(root, 0)];
+            while let Some(&mut (node, ref mut tried)) = calls.last_mut() {
+                let v = node as usize;
+                if *tried == 0 {
+                    index[v] = next_index;
+                    low[v] = next_index;
+                    next_index += 1;
+                    stack.push(node);
+                    on_stack[v] = true;
+                }
+                if *tried < 2 {
+                    let (left, right) = self.edges[v];
+                    let next = if *tried == 0 { left } else { right };
+                    *tried += 1;
+                    if index[next as usize] == NONE {
+                        calls.push((next, 0));
+                    } else if on_stack[next as usize] {
+                        low[v] = low[v].min(index[next as usize]);
+                    }
+                    continue;
+                }
+                calls.pop();
+                if let Some(&(parent, _)) = calls.last() {
+                    low[parent as usize] = low[parent as usize].min(low[v]);
+                }
+                if low[v] == index[v] {
+                    let mut component = vec![];
+                    loop {
+                        let w = stack.pop().unwrap();
+                        on_stack[w as usize] = false;
+                        component.push(w);
+                        if w == node {
+                            break;
+                        }
+                    }
+                    component.sort();
+                    components.push(component);
+                }
+            }
+        }
+        components.sort();
+        components
+    }
+
+    /// Every node reachable from `from` by any turns, `from` included, sorted.
+    pub fn reachable(&self, from: u32) -> Vec<u32> {
+        let mut seen = vec![false; self.len()];
+        seen[from as usize] = true;
+        let mut stack = vec![from];
+        while let Some(node) = stack.pop() {
+            let (left, right) = self.edges(node);
+            for next in [left, right] {
+                if !std::mem::replace(&mut seen[next as usize], true) {
+                    stack.push(next);
+                }
+            }
+        }
+        (0..self.len() as u32)
+            .filter(|&id| seen[id as usize])
+            .collect()
+    }
+
+    /// For each start node, the end nodes it can reach by any turns.
+    pub fn reachable_ends(&self) -> Vec<(u32, Vec<u32>)> {
+        self.starts()
+            .map(|start| {
+                let mut ends = self.reachable(start);
+                ends.retain(|&id| self.is_end(id));
+                (start, ends)
+            })
+            .collect()
+    }
+
+    /// The network as a Graphviz digraph, start nodes as diamonds and end nodes as squares.
+    pub fn to_graphviz(&self) -> String {
+        let mut s = String::new();
+        s.push_str("digraph {\n");
+        for (id, (left, right)) in self.edges.iter().enumerate() {
+            let name = &self.names[id];
+            s.push_str(&format!(
+                "  \"{}\" -> \"{}\" [label=L]\n",
+                name,
+                self.name(*left)
+            ));
+            s.push_str(&format!(
+                "  \"{}\" -> \"{}\" [label=R]\n",
+                name,
+                self.name(*right)
+            ));
+        }
+        for id in 0..self.len() as u32 {
+            if self.is_start(id) {
+                s.push_str(&format!("  \"{}\" [shape=Mdiamond]\n", self.name(id)));
+            } else if self.is_end(id) {
+                s.push_str(&format!("  \"{}\" [shape=Msquare]\n", self.name(id)));
+            }
+        }
+        s.push_str("}\n");
+        s
+    }
+}
+
+/// Reads the instruction line, an empty line and the `AAA = (BBB, CCC)` node lines, checking
+/// that every instruction is `L` or `R` and that every node referred to is defined.
+pub fn parse_input<R: std::io::BufRead>(reader: R) -> common::Result<(Vec<Turn>, Network)> {
+    let lines = reader.lines().collect::<Result<Vec<_>, _>>()?;
+    let first = common::Line::new(1, lines.first().map_or("", String::as_str));
+    if first.text.is_empty() {
+        return Err(first.error(first.text, "missing instructions").into());
+    }
+    let mut instructions = Vec::with_capacity(first.text.len());
+    for (i, c) in first.text.char_indices() {
+        instructions.push(match c {
+            'L' => Turn::Left,
+            'R' => Turn::Right,
+            _ => {
+                let at = &first.text[i..i + c.len_utf8()];
+                return Err(first.error(at, "unknown instruction").into());
+            }
+        });
+    }
+
+    if let Some(text) = lines.get(1) {
+        let line = common::Line::new(2, text);
+        if !line.text.trim().is_empty() {
+            return Err(line.error(line.text, "expected an empty line").into());
+        }
+    }
+
+    let mut network = Network::default();
+    let mut references = vec![];
+    for (i, text) in lines.iter().enumerate().skip(2) {
+        let line = common::Line::new(i + 1, text);
+        let (from, rest) = line.split_once(line.text, "=")?;
+        let (_, rest) = line.split_once(rest, "(")?;
+        let (left, rest) = line.split_once(rest, ",")?;
+        let (right, _) = line.split_once(rest, ")")?;
+        let (from, left, right) = (from.trim(), left.trim(), right.trim());
+        if network.ids.contains_key(from) {
+            return Err(line.error(from, "node defined twice").into());
+        }
+        network
+            .ids
+            .insert(from.to_string(), network.len().try_into().unwrap());
+        network.names.push(from.to_string());
+        references.push((
+            line.error(left, "undefined node"),
+            line.error(right, "undefined node"),
+        ));
+    }
+    for (left, right) in references {
+        let id = |r: common::ParseError| network.id(&r.text).ok_or(r);
+        let edges = (id(left)?, id(right)?);
+        network.edges.push(edges);
+    }
+    Ok((instructions, network))
+}
+
+pub fn part1<R: std::io::BufRead>(reader: R) -> common::Result<i64> {
+    let (instructions, network) = parse_input(reader)?;
+    let Some(start) = network.id("AAA") else {
+        return Err(common::Error::Invalid("no node AAA".into()));
+    };
+    let Some(end) = network.id("ZZZ") else {
+        return Err(common::Error::Invalid("no node ZZZ".into()));
+    };
+
+    // Once a node comes back at the same instruction, the walk repeats without reaching ZZZ.
+    let mut seen = std::collections::HashSet::new();
+    let mut current_node = start;
     let mut step_count = 0;
-    for instruction in instructions.chars().cycle() {
-        if current_node == "ZZZ" {
+    for (i, &turn) in instructions.iter().enumerate().cycle() {
+        if current_node == end {
             break;
         }
-
-        current_node = match instruction {
-            'L' => map.get(current_node).unwrap().0.as_str(),
-            'R' => map.get(current_node).unwrap().1.as_str(),
-            _ => panic!("Unknown instruction: {}", instruction),
-        };
+        if !seen.insert((current_node, i)) {
+            return Err(common::Error::Invalid(
+                "ZZZ cannot be reached from AAA".into(),
+            ));
+        }
+        current_node = network.step(current_node, turn);
         step_count += 1;
     }
 
-    step_count
+    Ok(step_count)
 }
 
+/// The nodes visited from a start node, split into the part before the walk starts repeating
+/// and the part that repeats.
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct NamesTrajectory<'a> {
-    head: Vec<&'a str>,
-    cycle: Vec<&'a str>,
+struct NamesTrajectory {
+    head: Vec<u32>,
+    cycle: Vec<u32>,
 }
 
-impl<'a> NamesTrajectory<'a> {
-    fn calculate(
-        map: &'a std::collections::HashMap<String, (String, String)>,
-        instructions: &[char],
-        start: &'a str,
-    ) -> Self {
+impl NamesTrajectory {
+    fn calculate(network: &Network, instructions: &[Turn], start: u32) -> Self {
+        // The walk repeats once a node comes back at the same instruction, and the cycle's
+        // length is a multiple of the instruction count, so it is enough to remember where
+        // each node was seen at the first instruction. That finds the cycle with a head up to
+        // one instruction string too long, which is trimmed afterwards.
+        let mut seen_at = vec![NONE; network.len()];
+        let mut head = Vec::new();
         let mut current_node = start;
-        let mut current_ip: usize = 0;
-        let mut head = Vec::<&str>::new();
-        let mut seen = std::collections::HashMap::<(&str, usize), usize>::new();
         loop {
-            if let Some(&loop_ip) = seen.get(&(current_node, current_ip)) {
-                let cycle = head.split_off(loop_ip);
+            let seen = &mut seen_at[current_node as usize];
+            if *seen != NONE {
+                let mut cycle = head.split_off(*seen as usize);
+                let mut extra = 0;
+                while extra < head.len()
+                    && head[head.len() - 1 - extra] == cycle[cycle.len() - 1 - extra]
+                {
+                    extra += 1;
+                }
+                head.truncate(head.len() - extra);
+                cycle.rotate_right(extra);
                 return NamesTrajectory { head, cycle };
             }
-            seen.insert((current_node, current_ip), head.len());
-            head.push(current_node);
-            match instructions[current_ip] {
-                'L' => current_node = map.get(current_node).unwrap().0.as_str(),
-                'R' => current_node = map.get(current_node).unwrap().1.as_str(),
-                _ => panic!("Unknown instruction: {}", instructions[current_ip]),
+            *seen = head.len().try_into().unwrap();
+            for &turn in instructions {
+                head.push(current_node);
+                current_node = network.step(current_node, turn);
             }
-            current_ip = (current_ip + 1) % instructions.len();
         }
     }
 }
 
+/// Where the walk from one start node goes when following the instructions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StartSummary {
+    pub start: u32,
+    /// Steps before the walk starts repeating.
+    pub head_len: usize,
+    /// Length of the repeating part, in steps and in passes over the instructions.
+    pub cycle_len: usize,
+    pub cycle_passes: usize,
+    /// The end nodes visited, in order of first visit.
+    pub ends: Vec<u32>,
+}
+
+pub fn summarize_starts(network: &Network, instructions: &[Turn]) -> Vec<StartSummary> {
+    network
+        .starts()
+        .map(|start| {
+            let trajectory = NamesTrajectory::calculate(network, instructions, start);
+            let mut ends: Vec<u32> = vec![];
+            for &id in trajectory.head.iter().chain(&trajectory.cycle) {
+                if network.is_end(id) && !ends.contains(&id) {
+                    ends.push(id);
+                }
+            }
+            StartSummary {
+                start,
+                head_len: trajectory.head.len(),
+                cycle_len: trajectory.cycle.len(),
+                cycle_passes: trajectory.cycle.len() / instructions.len(),
+                ends,
+            }
+        })
+        .collect()
+}
+
 type Index = u128;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -77,19 +348,19 @@ struct IndexesTrajectory {
 }
 
 impl IndexesTrajectory {
-    fn from_names(names_trajectory: &NamesTrajectory) -> Self {
+    fn from_names(network: &Network, names_trajectory: &NamesTrajectory) -> Self {
         let head: Vec<Index> = names_trajectory
             .head
             .iter()
             .enumerate()
-            .filter(|(_, &v)| v.ends_with('Z'))
+            .filter(|(_, &v)| network.is_end(v))
             .map(|(i, _)| i.try_into().unwrap())
             .collect();
         let cycle: Vec<Index> = names_trajectory
             .cycle
             .iter()
             .enumerate()
-            .filter(|(_, &v)| v.ends_with('Z'))
+            .filter(|(_, &v)| network.is_end(v))
             .map(|(i, _)| i.try_into().unwrap())
             .collect();
 
@@ -103,7 +374,7 @@ impl IndexesTrajectory {
 
     fn lengthen_head(&self, delta: Index) -> IndexesTrajectory {
         let mut head = self.head.clone();
-        let mut current_offset: Index = self.head.len().try_into().unwrap();
+        let mut current_offset = self.head_size;
         let mut remaining_delta = delta;
         loop {
             head.extend(
@@ -119,11 +390,12 @@ impl IndexesTrajectory {
                 current_offset += self.cycle_size;
             }
         }
-        let cycle: Vec<Index> = self
+        let mut cycle: Vec<Index> = self
             .cycle
             .iter()
             .map(|v| (v + self.cycle_size - delta % self.cycle_size) % self.cycle_size)
             .collect();
+        cycle.sort();
         IndexesTrajectory {
             head,
             head_size: self.head_size + delta,
@@ -132,6 +404,8 @@ impl IndexesTrajectory {
         }
     }
 
+    /// The steps on which both trajectories are on a Z node. Any number of Z hits per cycle
+    /// works: each pair of cycle hits is solved with the Chinese remainder theorem.
     fn merge(&self, other: &Self) -> Self {
         match self.head_size.cmp(&other.head_size) {
             std::cmp::Ordering::Less => {
@@ -147,49 +421,51 @@ impl IndexesTrajectory {
 
         let self_head_members: std::collections::HashSet<Index> =
             self.head.iter().cloned().collect();
-        let other_head_members: std::collections::HashSet<Index> =
-            other.head.iter().cloned().collect();
-        let mut head: Vec<Index> = self_head_members
-            .intersection(&other_head_members)
+        let mut head: Vec<Index> = other
+            .head
+            .iter()
+            .filter(|v| self_head_members.contains(v))
             .cloned()
             .collect();
         head.sort();
 
-        assert!(self.cycle.len() == 1);
-        assert!(other.cycle.len() == 1);
-        let a1: i128 = self.cycle[0].try_into().unwrap();
         let n1: i128 = self.cycle_size.try_into().unwrap();
-        let a2: i128 = other.cycle[0].try_into().unwrap();
         let n2: i128 = other.cycle_size.try_into().unwrap();
-        let num::integer::ExtendedGcd { gcd, x: m1, y: m2 } = n1.extended_gcd(&n2);
-        assert!(a1 % gcd == a2 % gcd);
-        assert!(m1 * n1 + m2 * n2 == gcd);
-        let x = a1 * m2 * n2 + a2 * m1 * n1;
-        assert!(x % gcd == 0);
-        let x = x / gcd;
-        let cycle_size = n1 * n2 / gcd;
-        let x = x.rem_euclid(cycle_size);
-        let x: Index = x.try_into().unwrap();
-        let cycle_size: Index = cycle_size.try_into().unwrap();
+        let num::integer::ExtendedGcd { gcd, x: m1, .. } = n1.extended_gcd(&n2);
+        let cycle_size = n1 / gcd * n2;
+        let mut cycle = vec![];
+        for &a1 in &self.cycle {
+            for &a2 in &other.cycle {
+                let (a1, a2): (i128, i128) = (a1.try_into().unwrap(), a2.try_into().unwrap());
+                if (a2 - a1) % gcd != 0 {
+                    continue;
+                }
+                // m1 * n1 / gcd is 1 modulo n2 / gcd, so this x is a1 modulo n1 and a2
+                // modulo n2.
+                let t = ((a2 - a1) / gcd * m1).rem_euclid(n2 / gcd);
+                cycle.push(Index::try_from(a1 + n1 * t).unwrap());
+            }
+        }
+        cycle.sort();
 
         IndexesTrajectory {
             head,
             head_size: self.head_size,
-            cycle: vec![x],
-            cycle_size,
+            cycle,
+            cycle_size: cycle_size.try_into().unwrap(),
         }
     }
 
-    #[allow(dead_code)]
+    /// `merge` by listing every step of the combined cycle.
     fn merge_slow(&self, other: &Self) -> Self {
         match self.head_size.cmp(&other.head_size) {
             std::cmp::Ordering::Less => {
                 return self
                     .lengthen_head(other.head_size - self.head_size)
-                    .merge(other);
+                    .merge_slow(other);
             }
             std::cmp::Ordering::Greater => {
-                return self.merge(&other.lengthen_head(self.head_size - other.head_size));
+                return self.merge_slow(&other.lengthen_head(self.head_size - other.head_size));
             }
             _ => {}
         }
@@ -227,91 +503,130 @@ impl IndexesTrajectory {
         }
     }
 
-    fn iter(&self) -> impl Iterator<Item = Index> + '_ {
-        let head_size = self.head_size;
-        let cycle_size = self.cycle_size;
-        self.head.iter().cloned().chain((0..).flat_map(move |i| {
-            self.cycle
-                .iter()
-                .cloned()
-                .map(move |v| head_size + v + i * cycle_size)
-        }))
+    /// The first step on a Z node, if there is one.
+    fn first(&self) -> Option<Index> {
+        self.head
+            .first()
+            .copied()
+            .or_else(|| self.cycle.first().map(|v| self.head_size + v))
+    }
+
+    /// Whether the Z nodes are reached exactly on the multiples of the cycle size, which is
+    /// what makes the LCM of the cycle sizes the answer.
+    fn hits_multiples_of_cycle_size(&self) -> bool {
+        self.head.is_empty()
+            && self.cycle.len() == 1
+            && self.head_size + self.cycle[0] == self.cycle_size
     }
 }
 
-pub fn part2_try1<R: std::io::BufRead>(reader: R) -> i128 {
-    let mut lines = reader.lines();
-    let instructions = lines.next().unwrap().unwrap();
-    lines.next().unwrap().unwrap();
-
-    let mut map = std::collections::HashMap::<String, (String, String)>::new();
-    for line in lines {
-        let line = line.unwrap();
-        let (from, rest) = line.split_once(' ').unwrap();
-        let (_, rest) = rest.split_once('(').unwrap();
-        let (left, rest) = rest.split_once(',').unwrap();
-        let (right, _) = rest.trim().split_once(')').unwrap();
-        map.insert(from.to_string(), (left.to_string(), right.to_string()));
-    }
-
-    let mut current_node_set: std::collections::HashSet<String> = map
-        .keys()
-        .filter(|node| node.ends_with('A'))
-        .cloned()
-        .collect();
-    let mut step_count = 0;
-    for instruction in instructions.chars().cycle() {
-        if current_node_set.iter().all(|node| node.ends_with('Z')) {
-            break;
-        }
+/// How `solve_ghosts` found its answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// Every start hits a Z node exactly on the multiples of its cycle length, so the
+    /// answer is the LCM of the cycle lengths.
+    Lcm,
+    /// The general intersection of every start's Z hits, head and cycle.
+    Crt,
+}
 
-        current_node_set = current_node_set
-            .into_iter()
-            .map(|node| match instruction {
-                'L' => map.get(&node).unwrap().0.clone(),
-                'R' => map.get(&node).unwrap().1.clone(),
-                _ => panic!("Unknown instruction: {}", instruction),
-            })
-            .collect();
-        step_count += 1;
-    }
+/// How `solve_ghosts` merges trajectories when it cannot use the LCM. Both give the same
+/// answers; slow lists every step of the combined cycles, so it is only usable on small
+/// inputs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Merge {
+    #[default]
+    Fast,
+    Slow,
+}
 
-    step_count
+/// The first step on which every ghost stands on a Z node, and how it was found.
+pub fn solve_ghosts<R: std::io::BufRead>(reader: R) -> common::Result<(i128, Method)> {
+    solve_ghosts_with(reader, Merge::default())
 }
 
-pub fn part2_try2<R: std::io::BufRead>(reader: R) -> i128 {
-    let mut lines = reader.lines();
-    let instructions = lines.next().unwrap().unwrap();
-    let instructions: Vec<char> = instructions.chars().collect();
-    lines.next().unwrap().unwrap();
-
-    let mut map = std::collections::HashMap::<String, (String, String)>::new();
-    for line in lines {
-        let line = line.unwrap();
-        let (from, rest) = line.split_once(' ').unwrap();
-        let (_, rest) = rest.split_once('(').unwrap();
-        let (left, rest) = rest.split_once(',').unwrap();
-        let (right, _) = rest.trim().split_once(')').unwrap();
-        map.insert(from.to_string(), (left.to_string(), right.to_string()));
-    }
-
-    let trajectories: Vec<IndexesTrajectory> = map
-        .keys()
-        .filter(|v| v.ends_with('A'))
-        .map(|v| NamesTrajectory::calculate(&map, instructions.as_slice(), v.as_str()))
-        .map(|t| IndexesTrajectory::from_names(&t))
+pub fn solve_ghosts_with<R: std::io::BufRead>(
+    reader: R,
+    how: Merge,
+) -> common::Result<(i128, Method)> {
+    let (instructions, network) = parse_input(reader)?;
+
+    let trajectories: Vec<IndexesTrajectory> = network
+        .starts()
+        .map(|v| NamesTrajectory::calculate(&network, &instructions, v))
+        .map(|t| IndexesTrajectory::from_names(&network, &t))
         .collect();
+    if trajectories.is_empty() {
+        return Err(common::Error::Invalid("no node ends with A".into()));
+    }
 
-    eprintln!("Trajectories: {:#?}", trajectories);
+    if trajectories
+        .iter()
+        .all(IndexesTrajectory::hits_multiples_of_cycle_size)
+    {
+        let lcm = trajectories
+            .iter()
+            .fold(1, |lcm: Index, t| lcm.lcm(&t.cycle_size));
+        return Ok((lcm.try_into().unwrap(), Method::Lcm));
+    }
 
-    let merged = trajectories
+    let merged = trajectories[1..]
         .iter()
-        .skip(1)
-        .fold(trajectories.first().unwrap().clone(), |a, b| a.merge(b));
+        .fold(trajectories[0].clone(), |a, b| match how {
+            Merge::Fast => a.merge(b),
+            Merge::Slow => a.merge_slow(b),
+        });
+    let first = merged.first().ok_or_else(|| {
+        common::Error::Invalid("the ghosts are never all on Z nodes at once".into())
+    })?;
+    Ok((first.try_into().unwrap(), Method::Crt))
+}
+
+pub fn part2<R: std::io::BufRead>(reader: R) -> common::Result<i128> {
+    Ok(solve_ghosts(reader)?.0)
+}
 
-    let rv = merged.iter().next().unwrap().try_into().unwrap();
+/// `merge=fast|slow` picks how part 2 merges trajectories.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Ghosts {
+    pub merge: Merge,
+}
 
-    rv
+impl common::Params for Ghosts {
+    fn set(&mut self, name: &str, value: &str) -> common::Result<()> {
+        match name {
+            "merge" => {
+                self.merge = match value.trim() {
+                    "fast" => Merge::Fast,
+                    "slow" => Merge::Slow,
+                    _ => {
+                        return Err(common::Error::Invalid(format!(
+                            "parameter {name}={value}: expected fast or slow"
+                        )))
+                    }
+                }
+            }
+            _ => return Err(common::Error::unknown_param(name)),
+        }
+        Ok(())
+    }
+}
+
+pub struct Day08;
+
+impl common::Solver for Day08 {
+    type Params1 = ();
+    type Params2 = Ghosts;
+    type Answer1 = i64;
+    type Answer2 = i128;
+
+    fn part1(input: &str, _: &()) -> common::Result<i64> {
+        part1(input.as_bytes())
+    }
+
+    fn part2(input: &str, params: &Ghosts) -> common::Result<i128> {
+        Ok(solve_ghosts_with(input.as_bytes(), params.merge)?.0)
+    }
 }
 
 #[cfg(test)]
@@ -319,27 +634,33 @@ mod tests {
     use crate::NamesTrajectory;
 
     #[test]
-    fn test_names_trajectory() {
-        let map = [
-            ("A", ("B", "C")),
-            ("B", ("A", "B")),
-            ("C", ("C", "A")),
-            ("Z", ("Z", "A")),
-        ]
-        .into_iter()
-        .map(|(a, (b, c))| (a.to_string(), (b.to_string(), c.to_string())))
-        .collect();
-        let trajectory = NamesTrajectory::calculate(&map, &['L', 'R'], "Z");
+    fn test_names_trajectory() -> common::Result<()> {
+        let input = "LR\n\nA = (B, C)\nB = (A, B)\nC = (C, A)\nZ = (Z, A)\nY = (A, A)\n";
+        let (instructions, network) = super::parse_input(input.as_bytes())?;
+        let id = |name| network.id(name).unwrap();
+        let trajectory = NamesTrajectory::calculate(&network, &instructions, id("Z"));
 
         // LRLRLRLRLRL
         // ZZ[ABBACC]
         assert_eq!(
             trajectory,
             NamesTrajectory {
-                head: vec!["Z", "Z"],
-                cycle: vec!["A", "B", "B", "A", "C", "C"],
+                head: ["Z", "Z"].map(id).to_vec(),
+                cycle: ["A", "B", "B", "A", "C", "C"].map(id).to_vec(),
             }
         );
+
+        // LRLRLRLR
+        // Y[ACCABB], the cycle starting on an R
+        let trajectory = NamesTrajectory::calculate(&network, &instructions, id("Y"));
+        assert_eq!(
+            trajectory,
+            NamesTrajectory {
+                head: vec![id("Y")],
+                cycle: ["A", "C", "C", "A", "B", "B"].map(id).to_vec(),
+            }
+        );
+        Ok(())
     }
 
     #[test]
@@ -372,6 +693,52 @@ mod tests {
             cycle_size: 12,
         };
         assert_eq!(merged_trajectory, expected_trajectory);
+        assert_eq!(trajectory1.merge(&trajectory2), expected_trajectory);
+
+        // Lengthening by more steps than there are hits in the head.
+        let trajectory3 = super::IndexesTrajectory {
+            // 01 01234
+            // 10[01001]
+            head: vec![0],
+            head_size: 2,
+            cycle: vec![1, 4],
+            cycle_size: 5,
+        };
+        let expected: Vec<super::Index> = (0..200)
+            .filter(|&i| hits(&trajectory1, i) && hits(&trajectory3, i))
+            .collect();
+        for merged in [
+            trajectory1.merge(&trajectory3),
+            trajectory3.merge(&trajectory1),
+        ] {
+            let actual: Vec<super::Index> = (0..200).filter(|&i| hits(&merged, i)).collect();
+            assert_eq!(actual, expected);
+            assert_eq!(merged, trajectory1.merge_slow(&trajectory3));
+        }
+    }
+
+    fn hits(trajectory: &super::IndexesTrajectory, step: super::Index) -> bool {
+        if step < trajectory.head_size {
+            trajectory.head.contains(&step)
+        } else {
+            let offset = (step - trajectory.head_size) % trajectory.cycle_size;
+            trajectory.cycle.contains(&offset)
+        }
+    }
+
+    /// The first step on which every ghost is on a Z node, by walking them all.
+    fn walk_ghosts(input: &str, limit: usize) -> common::Result<Option<usize>> {
+        let (instructions, network) = super::parse_input(input.as_bytes())?;
+        let mut ghosts: Vec<u32> = network.starts().collect();
+        for (step, &turn) in instructions.iter().cycle().take(limit).enumerate() {
+            if ghosts.iter().all(|&g| network.is_end(g)) {
+                return Ok(Some(step));
+            }
+            for g in &mut ghosts {
+                *g = network.step(*g, turn);
+            }
+        }
+        Ok(None)
     }
 
     const EXAMPLE1: &str = "\
@@ -395,24 +762,27 @@ ZZZ = (ZZZ, ZZZ)
 ";
 
     #[test]
-    fn test_part1_example1() {
+    fn test_part1_example1() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
-        let answer = super::part1(reader);
+        let answer = super::part1(reader)?;
         assert_eq!(answer, 2);
+        Ok(())
     }
 
     #[test]
-    fn test_part1_example2() {
+    fn test_part1_example2() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE2.as_bytes());
-        let answer = super::part1(reader);
+        let answer = super::part1(reader)?;
         assert_eq!(answer, 6);
+        Ok(())
     }
 
     #[test]
-    fn test_part1_final() {
+    fn test_part1_final() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_bytes!("final.txt").as_slice());
-        let answer = super::part1(reader);
+        let answer = super::part1(reader)?;
         assert_eq!(answer, 13771);
+        Ok(())
     }
 
     const EXAMPLE3: &str = "\
@@ -429,16 +799,169 @@ XXX = (XXX, XXX)
 ";
 
     #[test]
-    fn test_part2_example() {
+    fn test_part2_example() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE3.as_bytes());
-        let answer = super::part2_try1(reader);
-        assert_eq!(answer, 6);
+        let answer = super::solve_ghosts(reader)?;
+        assert_eq!(answer, (6, super::Method::Crt));
+        Ok(())
+    }
+
+    #[test]
+    fn test_network_analysis() -> common::Result<()> {
+        let (instructions, network) = super::parse_input(EXAMPLE3.as_bytes())?;
+        let id = |name: &str| network.id(name).unwrap();
+        let ids = |names: &[&str]| names.iter().map(|&name| id(name)).collect::<Vec<_>>();
+
+        let sccs = network.sccs();
+        assert_eq!(
+            sccs,
+            [
+                ids(&["11A"]),
+                ids(&["11B", "11Z"]),
+                ids(&["22A"]),
+                ids(&["22B", "22C", "22Z"]),
+                ids(&["XXX"]),
+            ]
+        );
+        assert_eq!(
+            network.reachable_ends(),
+            [(id("11A"), ids(&["11Z"])), (id("22A"), ids(&["22Z"]))]
+        );
+
+        let summaries = super::summarize_starts(&network, &instructions);
+        let summary = |s: &super::StartSummary| (s.head_len, s.cycle_len, s.cycle_passes);
+        assert_eq!(
+            summaries.iter().map(summary).collect::<Vec<_>>(),
+            [(1, 2, 1), (1, 6, 3)]
+        );
+        assert_eq!(summaries[1].ends, ids(&["22Z"]));
+
+        let dot = network.to_graphviz();
+        assert!(dot.contains("  \"11A\" -> \"11B\" [label=L]\n"));
+        assert!(dot.contains("  \"11A\" [shape=Mdiamond]\n"));
+        assert!(dot.contains("  \"22Z\" [shape=Msquare]\n"));
+        Ok(())
     }
 
+    // 11A is on a Z node on steps 2, 4, 7, 9, 12, ... and 22A on steps 3, 6, 9, ..., so
+    // the LCM of the cycle lengths (15) would be wrong.
+    const EXAMPLE4: &str = "\
+L
+
+11A = (11B, 11B)
+11B = (11Z, 11Z)
+11Z = (11C, 11C)
+11C = (1YZ, 1YZ)
+1YZ = (11D, 11D)
+11D = (11E, 11E)
+11E = (11Z, 11Z)
+22A = (22B, 22B)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22D, 22D)
+22D = (22E, 22E)
+22E = (22Z, 22Z)
+";
+
     #[test]
-    fn test_part2_final() {
+    fn test_part2_unaligned() -> common::Result<()> {
+        let answer = super::solve_ghosts(EXAMPLE4.as_bytes())?;
+        assert_eq!(answer, (9, super::Method::Crt));
+        // 22A's cycle grows to five steps, on Z on steps 3, 8, 13, ...
+        let never = EXAMPLE4.replace(
+            "22E = (22Z, 22Z)",
+            "22E = (22F, 22F)\n22F = (22G, 22G)\n22G = (22Z, 22Z)",
+        );
+        assert!(super::part2(never.as_bytes()).is_err());
+        Ok(())
+    }
+
+    // 2A is on a Z node in its head, and the heads are of different lengths.
+    const EXAMPLE5: &str = "\
+L
+
+1A = (1B, 1B)
+1B = (1C, 1C)
+1C = (1Z, 1Z)
+1Z = (1D, 1D)
+1D = (1Z, 1Z)
+2A = (2B, 2B)
+2B = (2Z, 2Z)
+2Z = (2C, 2C)
+2C = (2D, 2D)
+2D = (2E, 2E)
+2E = (2F, 2F)
+2F = (2GZ, 2GZ)
+2GZ = (2F, 2F)
+";
+
+    #[test]
+    fn test_part2_matches_walk() -> common::Result<()> {
+        assert_eq!(
+            super::solve_ghosts(EXAMPLE5.as_bytes())?,
+            (7, super::Method::Crt)
+        );
+        for input in [EXAMPLE3, EXAMPLE4, EXAMPLE5, include_str!("ghosts.txt")] {
+            let expected = walk_ghosts(input, 1000)?.map(|step| step as i128);
+            assert_eq!(Some(super::part2(input.as_bytes())?), expected, "{input}");
+            let slow = super::solve_ghosts_with(input.as_bytes(), super::Merge::Slow)?;
+            assert_eq!(Some(slow.0), expected, "{input}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_part2_final() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("final.txt").as_bytes());
-        let answer = super::part2_try2(reader);
-        assert_eq!(answer, 13129439557681);
+        let answer = super::solve_ghosts(reader)?;
+        assert_eq!(answer, (13129439557681, super::Method::Lcm));
+        Ok(())
+    }
+
+    #[test]
+    fn test_undefined_node() {
+        let input = "LR\n\nAAA = (AAA, BBB)\n";
+        let Err(common::Error::Parse(err)) = super::part1(input.as_bytes()) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!((err.line, err.column, err.text.as_str()), (3, 13, "BBB"));
+
+        let input = "LR\n\nAAA = (AAA, AAA)\nAAA = (AAA, AAA)\n";
+        let Err(common::Error::Parse(err)) = super::part1(input.as_bytes()) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(
+            (err.line, err.column, err.message.as_str()),
+            (4, 1, "node defined twice")
+        );
+
+        let input = "LR\nAAA = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)\n";
+        let Err(common::Error::Parse(err)) = super::part1(input.as_bytes()) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(
+            (err.line, err.column, err.message.as_str()),
+            (2, 1, "expected an empty line")
+        );
+    }
+
+    #[test]
+    fn test_part1_without_route() {
+        for (input, expected) in [
+            ("L\n\nAAA = (AAA, AAA)\n", "no node ZZZ"),
+            (
+                "L\n\nAAA = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)\n",
+                "ZZZ cannot be reached from AAA",
+            ),
+            (
+                "LR\n\nAAA = (BBB, ZZZ)\nBBB = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)\n",
+                "ZZZ cannot be reached from AAA",
+            ),
+        ] {
+            let Err(common::Error::Invalid(message)) = super::part1(input.as_bytes()) else {
+                panic!("expected an invalid input");
+            };
+            assert_eq!(message, expected);
+        }
     }
 }
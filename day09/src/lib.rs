@@ -1,53 +1,227 @@
-use std::io::Write;
+use num::rational::Ratio;
 
-pub fn part1<R: std::io::BufRead>(reader: R) -> i64 {
-    let mut answer = 0;
-    for line in reader.lines() {
-        let line = line.unwrap();
-        let mut vals: Vec<Vec<i64>> = vec![line
+/// Fits a sequence to every line, with `check` also making sure that each fit gives back the
+/// line's values.
+fn read_sequences<R: std::io::BufRead>(reader: R, check: bool) -> common::Result<Vec<Sequence>> {
+    let mut sequences = vec![];
+    for (i, text) in reader.lines().enumerate() {
+        let text = text?;
+        let line = common::Line::new(i + 1, &text);
+        let values = line
+            .text
             .split_ascii_whitespace()
-            .map(|s| s.parse::<i64>().unwrap())
-            .collect()];
-        while vals
-            .last()
-            .unwrap()
-            .iter()
-            .any(|v| v != vals.last().unwrap().first().unwrap())
-        {
-            vals.push(
-                vals.last()
-                    .unwrap()
-                    .iter()
-                    .zip(vals.last().unwrap().iter().skip(1))
-                    .map(|(a, b)| b - a)
-                    .collect(),
-            )
+            .map(|s| line.parse::<i64>(s))
+            .collect::<Result<Vec<_>, _>>()?;
+        if values.is_empty() {
+            return Err(line.error(line.text, "empty sequence").into());
         }
-        let v = *vals.last().unwrap().last().unwrap();
-        vals.last_mut().unwrap().push(v);
-        for i in (0..(vals.len() - 1)).rev() {
-            let v = vals[i].last().unwrap() + vals[i + 1].last().unwrap();
-            vals[i].push(v);
+        let sequence = Sequence::new(&values).map_err(|err| common::Error::AtLine {
+            line: line.number,
+            source: err.into(),
+        })?;
+        if check {
+            sequence
+                .check(&values)
+                .map_err(|err| line.error(line.text, err.to_string()))?;
         }
-        answer += vals[0].last().unwrap();
+        sequences.push(sequence);
     }
-    answer
+    Ok(sequences)
 }
 
-pub fn part2<R: std::io::BufRead>(reader: R) -> i64 {
-    let mut writer = std::io::Cursor::new(Vec::<u8>::new());
-    for line in reader.lines() {
-        let rev_line: String = line
-            .unwrap()
-            .trim()
-            .split_ascii_whitespace()
+/// Values at 0, 1, 2, ... of a polynomial, kept as the Newton forward differences at 0 so
+/// the value at any index can be computed directly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sequence {
+    len: usize,
+    /// The first entry of each row of the difference table, down to the first constant row.
+    leading: Vec<i128>,
+}
+
+impl Sequence {
+    /// Fails unless some row of differences is constant while still holding at least two
+    /// entries, that is unless the values fit a polynomial of degree below `values.len() - 1`.
+    pub fn new(values: &[i64]) -> Result<Self, NotPolynomial> {
+        let mut row: Vec<i128> = values.iter().map(|&v| v.into()).collect();
+        let mut leading = vec![];
+        while row.len() >= 2 {
+            leading.push(row[0]);
+            if row.iter().all(|&v| v == row[0]) {
+                return Ok(Sequence {
+                    len: values.len(),
+                    leading,
+                });
+            }
+            row = row.windows(2).map(|w| w[1] - w[0]).collect();
+        }
+        Err(NotPolynomial {
+            len: values.len(),
+            depth: leading.len(),
+        })
+    }
+
+    /// Checks that the polynomial gives back every one of `values`.
+    pub fn check(&self, values: &[i64]) -> common::Result<()> {
+        for (x, &expected) in (0..).zip(values) {
+            let actual = self.value_at(x);
+            if actual != i128::from(expected) {
+                return Err(common::Error::Invalid(format!(
+                    "the polynomial gives {actual} at index {x}, not {expected}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn degree(&self) -> usize {
+        self.leading.len() - 1
+    }
+
+    /// The value at index `x`, which may be negative or past the end.
+    pub fn value_at(&self, x: i64) -> i128 {
+        let x = i128::from(x);
+        // binomial is x choose j, and x choose j times (x - j) is always divisible by j + 1.
+        let mut binomial = 1;
+        let mut value = 0;
+        for (j, &difference) in (0..).zip(&self.leading) {
+            value += difference * binomial;
+            binomial = binomial * (x - j) / (j + 1);
+        }
+        value
+    }
+
+    /// The value `k` steps after the last one.
+    pub fn forward(&self, k: i64) -> i128 {
+        self.value_at(i64::try_from(self.len).unwrap() - 1 + k)
+    }
+
+    /// The value `k` steps before the first one.
+    pub fn backward(&self, k: i64) -> i128 {
+        self.value_at(-k)
+    }
+
+    /// The polynomial in the index that gives the values.
+    pub fn polynomial(&self) -> Polynomial {
+        let mut coefficients = vec![Ratio::from_integer(0); self.leading.len()];
+        // x (x - 1) ... (x - j + 1), lowest power first.
+        let mut falling = vec![1];
+        let mut factorial = 1;
+        for (j, &difference) in (0..).zip(&self.leading) {
+            for (c, &f) in coefficients.iter_mut().zip(&falling) {
+                *c += Ratio::new(difference * f, factorial);
+            }
+            let mut next = vec![0; falling.len() + 1];
+            for (i, &f) in falling.iter().enumerate() {
+                next[i + 1] += f;
+                next[i] -= j * f;
+            }
+            falling = next;
+            factorial *= j + 1;
+        }
+        while coefficients.len() > 1 && coefficients.last() == Some(&Ratio::from_integer(0)) {
+            coefficients.pop();
+        }
+        Polynomial { coefficients }
+    }
+}
+
+/// The differences of `len` values never became constant; `depth` rows were computed before
+/// only one entry was left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotPolynomial {
+    pub len: usize,
+    pub depth: usize,
+}
+
+impl std::fmt::Display for NotPolynomial {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "not a polynomial: no constant differences in {} rows from {} values",
+            self.depth, self.len
+        )
+    }
+}
+
+impl std::error::Error for NotPolynomial {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    /// Lowest power first.
+    pub coefficients: Vec<Ratio<i128>>,
+}
+
+impl Polynomial {
+    pub fn eval(&self, x: i64) -> Ratio<i128> {
+        let x = Ratio::from_integer(i128::from(x));
+        self.coefficients
+            .iter()
             .rev()
-            .collect::<Vec<_>>()
-            .join(" ");
-        writeln!(writer, "{}", rev_line).unwrap();
+            .fold(Ratio::from_integer(0), |acc, &c| acc * x + c)
+    }
+}
+
+pub fn part1<R: std::io::BufRead>(reader: R) -> common::Result<i128> {
+    part1_with(reader, false)
+}
+
+pub fn part1_with<R: std::io::BufRead>(reader: R, check: bool) -> common::Result<i128> {
+    Ok(read_sequences(reader, check)?
+        .iter()
+        .map(|sequence| sequence.forward(1))
+        .sum())
+}
+
+pub fn part2<R: std::io::BufRead>(reader: R) -> common::Result<i128> {
+    part2_with(reader, false)
+}
+
+pub fn part2_with<R: std::io::BufRead>(reader: R, check: bool) -> common::Result<i128> {
+    Ok(read_sequences(reader, check)?
+        .iter()
+        .map(|sequence| sequence.backward(1))
+        .sum())
+}
+
+/// `check=true` makes sure each fitted polynomial gives back its line's values.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Check {
+    pub check: bool,
+}
+
+impl common::Params for Check {
+    fn set(&mut self, name: &str, value: &str) -> common::Result<()> {
+        match name {
+            "check" => self.check = common::parse_param(name, value)?,
+            _ => return Err(common::Error::unknown_param(name)),
+        }
+        Ok(())
+    }
+}
+
+pub struct Day09;
+
+impl common::Solver for Day09 {
+    type Params1 = Check;
+    type Params2 = Check;
+    type Answer1 = i128;
+    type Answer2 = i128;
+
+    fn part1(input: &str, params: &Check) -> common::Result<i128> {
+        part1_with(input.as_bytes(), params.check)
+    }
+
+    fn part2(input: &str, params: &Check) -> common::Result<i128> {
+        part2_with(input.as_bytes(), params.check)
     }
-    writer.set_position(0);
-    part1(writer)
 }
 
 #[cfg(test)]
@@ -59,30 +233,69 @@ mod tests {
 ";
 
     #[test]
-    fn test_part1_example() {
+    fn test_part1_example() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
-        let answer = super::part1(reader);
+        let answer = super::part1(reader)?;
         assert_eq!(answer, 114);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sequence() {
+        let sequence = super::Sequence::new(&[1, 3, 6, 10, 15, 21]).unwrap();
+        assert_eq!(sequence.degree(), 2);
+        assert_eq!([1, 3].map(|k| sequence.forward(k)), [28, 45]);
+        assert_eq!([1, 2, 3].map(|k| sequence.backward(k)), [0, 0, 1]);
+
+        let polynomial = sequence.polynomial();
+        let ratio = num::rational::Ratio::new;
+        assert_eq!(
+            polynomial.coefficients,
+            [ratio(1, 1), ratio(3, 2), ratio(1, 2)]
+        );
+        for x in [-7, 0, 5, 100] {
+            assert_eq!(polynomial.eval(x), sequence.value_at(x).into());
+        }
+    }
+
+    #[test]
+    fn test_not_polynomial() {
+        let err = super::Sequence::new(&[1, 2, 4, 8, 16]).unwrap_err();
+        assert_eq!((err.len, err.depth), (5, 4));
+        assert_eq!(super::Sequence::new(&[7]).unwrap_err().depth, 0);
+        assert_eq!(super::Sequence::new(&[7, 7]).map(|s| s.degree()), Ok(0));
+
+        let Err(common::Error::AtLine { line, source }) =
+            super::part2_with("1 2 3\n1 2 4 8 16\n".as_bytes(), true)
+        else {
+            panic!("expected an error at a line");
+        };
+        assert_eq!(line, 2);
+        let err = source.downcast_ref::<super::NotPolynomial>().unwrap();
+        assert_eq!((err.len, err.depth), (5, 4));
     }
 
     #[test]
-    fn test_part1_big() {
+    fn test_part1_big() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
-        let answer = super::part1(reader);
+        let answer = super::part1(reader)?;
         assert_eq!(answer, 1916822650);
+        Ok(())
     }
 
     #[test]
-    fn test_part2_example() {
+    fn test_part2_example() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
-        let answer = super::part2(reader);
+        let answer = super::part2(reader)?;
         assert_eq!(answer, 2);
+        Ok(())
     }
 
     #[test]
-    fn test_part2_big() {
+    fn test_part2_big() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
-        let answer = super::part2(reader);
+        let answer = super::part2(reader)?;
         assert_eq!(answer, 966);
+        Ok(())
     }
 }
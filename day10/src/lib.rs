@@ -1,82 +1,157 @@
-const MAX_N: usize = 200;
+/// The (row, column) steps to the tiles a pipe connects.
+fn pipe_deltas(tile: char) -> &'static [(i32, i32)] {
+    match tile {
+        '|' => &[(-1, 0), (1, 0)],
+        '-' => &[(0, -1), (0, 1)],
+        'L' => &[(-1, 0), (0, 1)],
+        'J' => &[(-1, 0), (0, -1)],
+        '7' => &[(0, -1), (1, 0)],
+        'F' => &[(0, 1), (1, 0)],
+        'S' => &[(-1, 0), (0, -1), (0, 1), (1, 0)],
+        _ => &[],
+    }
+}
 
+/// A pipe maze. Anything that is not a pipe, like the I/O markers in the examples, is ground.
 #[derive(Debug, Clone)]
-struct Map {
+pub struct Map {
     n_rows: usize,
     n_cols: usize,
     start: (usize, usize),
-    mtx: [[char; MAX_N]; MAX_N],
-    dists: [[i32; MAX_N]; MAX_N],
+    /// The shapes `S` could have had.
+    start_shapes: Vec<char>,
+    mtx: Vec<Vec<char>>,
+    dists: Vec<Vec<i32>>,
 }
 
 impl Map {
-    fn from_lines(lines: impl Iterator<Item = String>) -> Self {
-        let mut n_cols = 0;
-        let mut n_rows = 0;
-        let mut mtx: [[char; MAX_N]; MAX_N] = [['.'; MAX_N]; MAX_N];
-        for (r, line) in lines.enumerate() {
-            let line = line.trim();
-            n_cols = line.len();
-            n_rows = r + 1;
-            for (c, ch) in line.chars().enumerate() {
-                mtx[r][c] = ch;
+    pub fn read<R: std::io::BufRead>(reader: R) -> common::Result<Self> {
+        Self::read_with(reader, StartShape::default())
+    }
+
+    pub fn read_with<R: std::io::BufRead>(reader: R, choice: StartShape) -> common::Result<Self> {
+        Self::from_lines(reader.lines(), choice)
+    }
+
+    fn from_lines(
+        lines: impl Iterator<Item = std::io::Result<String>>,
+        choice: StartShape,
+    ) -> common::Result<Self> {
+        let mut mtx: Vec<Vec<char>> = vec![];
+        for (r, text) in lines.enumerate() {
+            let text = text?;
+            let line = common::Line::new(r + 1, text.trim());
+            let row: Vec<char> = line.text.chars().collect();
+            if let Some(first) = mtx.first() {
+                if row.len() != first.len() {
+                    let message = format!("expected {} columns", first.len());
+                    return Err(line.error(line.text, message).into());
+                }
             }
+            mtx.push(row);
         }
+        let (n_rows, n_cols) = (mtx.len(), mtx.first().map_or(0, Vec::len));
         let mut map = Map {
             n_rows,
             n_cols,
             start: (usize::MAX, usize::MAX),
+            start_shapes: vec![],
             mtx,
-            dists: [[-1; MAX_N]; MAX_N],
+            dists: vec![vec![-1; n_cols]; n_rows],
         };
-        map.adjust_start();
-        map
+        map.adjust_start(choice)?;
+        Ok(map)
     }
 
-    fn adjust_start(&mut self) {
-        for r in 0..self.n_rows {
-            for c in 0..self.n_cols {
-                if self.mtx[r][c] == 'S' {
-                    self.start = (r, c);
-                    let mut actual_neighbors: Vec<(i32, i32)> = self
-                        .neighbors((r, c))
-                        .filter_map(|(cr, cc)| {
-                            if self.neighbors((cr, cc)).any(|p| p == (r, c)) {
-                                Some((cr as i32 - r as i32, cc as i32 - c as i32))
-                            } else {
-                                None
-                            }
-                        })
-                        .collect();
-                    actual_neighbors.sort();
-                    self.mtx[r][c] = match actual_neighbors.as_slice() {
-                        [(-1, 0), (1, 0)] => '|',
-                        [(0, -1), (0, 1)] => '-',
-                        [(-1, 0), (0, 1)] => 'L',
-                        [(-1, 0), (0, -1)] => 'J',
-                        [(0, -1), (1, 0)] => '7',
-                        [(0, 1), (1, 0)] => 'F',
-                        _ => panic!("Invalid map char: {:?}", self.mtx[r][c]),
-                    };
-                    return;
+    /// Reads the map along with what `validate` finds on it. An `S` with several possible
+    /// shapes is one of the issues rather than an error, and takes the shape that closes the
+    /// longest loop.
+    pub fn read_validated<R: std::io::BufRead>(reader: R) -> common::Result<(Self, Vec<Issue>)> {
+        let map = Self::read_with(reader, StartShape::Largest)?;
+        let issues = map.validate();
+        Ok((map, issues))
+    }
+
+    pub fn n_rows(&self) -> usize {
+        self.n_rows
+    }
+
+    pub fn n_cols(&self) -> usize {
+        self.n_cols
+    }
+
+    pub fn start(&self) -> (usize, usize) {
+        self.start
+    }
+
+    /// Replaces `S` with a pipe that connects two of the neighbors pointing back at it,
+    /// picked by `choice` when there are more than two.
+    fn adjust_start(&mut self, choice: StartShape) -> common::Result<()> {
+        let mut tiles = (0..self.n_rows).flat_map(|r| (0..self.n_cols).map(move |c| (r, c)));
+        let Some((r, c)) = tiles.find(|&(r, c)| self.mtx[r][c] == 'S') else {
+            return Err(common::Error::Invalid("no start tile".into()));
+        };
+        self.start = (r, c);
+        let mut actual_neighbors: Vec<(i32, i32)> = self
+            .connections((r, c))
+            .map(|(cr, cc)| (cr as i32 - r as i32, cc as i32 - c as i32))
+            .collect();
+        actual_neighbors.sort();
+        let mut shapes = vec![];
+        for (i, &a) in actual_neighbors.iter().enumerate() {
+            for &b in &actual_neighbors[i + 1..] {
+                shapes.push(match (a, b) {
+                    ((-1, 0), (1, 0)) => '|',
+                    ((0, -1), (0, 1)) => '-',
+                    ((-1, 0), (0, 1)) => 'L',
+                    ((-1, 0), (0, -1)) => 'J',
+                    ((0, -1), (1, 0)) => '7',
+                    _ => 'F',
+                });
+            }
+        }
+        self.start_shapes = shapes.clone();
+        match (shapes.as_slice(), choice) {
+            ([], _) => {
+                return Err(common::Error::Invalid(format!(
+                    "start at row {}, column {} connects to {} pipes",
+                    r + 1,
+                    c + 1,
+                    actual_neighbors.len()
+                )))
+            }
+            ([shape], _) => self.mtx[r][c] = *shape,
+            (_, StartShape::Unique) => {
+                let issue = Issue::AmbiguousStart {
+                    at: (r, c),
+                    shapes: self.start_shapes.clone(),
+                };
+                return Err(common::Error::Invalid(issue.to_string()));
+            }
+            (_, StartShape::Largest) => {
+                let mut best = (0, shapes[0]);
+                for &shape in &shapes {
+                    self.mtx[r][c] = shape;
+                    if let Ok(main_loop) = self.trace_loop() {
+                        best = best.max((main_loop.tiles.len(), shape));
+                    }
                 }
+                self.mtx[r][c] = best.1;
             }
         }
+        Ok(())
+    }
+
+    /// The neighbors that `(r, c)` points at and that point back at it.
+    fn connections(&self, (r, c): (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.neighbors((r, c))
+            .filter(move |&n| self.neighbors(n).any(|p| p == (r, c)))
     }
 
     fn neighbors(&self, (r, c): (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
-        let deltas: Vec<(i32, i32)> = match self.mtx[r][c] {
-            '|' => vec![(-1, 0), (1, 0)],
-            '-' => vec![(0, -1), (0, 1)],
-            'L' => vec![(-1, 0), (0, 1)],
-            'J' => vec![(-1, 0), (0, -1)],
-            '7' => vec![(0, -1), (1, 0)],
-            'F' => vec![(0, 1), (1, 0)],
-            'S' => vec![(-1, 0), (0, -1), (0, 1), (1, 0)],
-            _ => vec![],
-        };
+        let deltas = pipe_deltas(self.mtx[r][c]);
         let (n_rows, n_cols) = (self.n_rows, self.n_cols);
-        deltas.into_iter().filter_map(move |(dr, dc)| {
+        deltas.iter().filter_map(move |&(dr, dc)| {
             let nr = r as i32 + dr;
             let nc = c as i32 + dc;
             if 0 <= nr && nr < n_rows as i32 && 0 <= nc && nc < n_cols as i32 {
@@ -112,6 +187,95 @@ impl Map {
         }
     }
 
+    /// Follows the pipes from the start back to it.
+    pub fn trace_loop(&self) -> common::Result<Loop> {
+        let mut tiles = vec![self.start];
+        let mut prev = self.start;
+        let mut current = self.neighbors(self.start).next().unwrap();
+        while current != self.start {
+            tiles.push(current);
+            match self.neighbors(current).find(|&n| n != prev) {
+                Some(next) if self.neighbors(next).any(|p| p == current) => {
+                    prev = current;
+                    current = next;
+                }
+                _ => {
+                    let (r, c) = current;
+                    return Err(common::Error::Invalid(format!(
+                        "the loop breaks at row {}, column {}",
+                        r + 1,
+                        c + 1
+                    )));
+                }
+            }
+        }
+        Ok(Loop { tiles })
+    }
+
+    /// Everything that is off about the map: an `S` that could have had several shapes, pipe
+    /// ends next to the start's pipes that lead nowhere, and loops besides the main one.
+    pub fn validate(&self) -> Vec<Issue> {
+        let mut issues = vec![];
+        if self.start_shapes.len() > 1 {
+            issues.push(Issue::AmbiguousStart {
+                at: self.start,
+                shapes: self.start_shapes.clone(),
+            });
+        }
+
+        let mut seen = vec![vec![false; self.n_cols]; self.n_rows];
+        for r in 0..self.n_rows {
+            for c in 0..self.n_cols {
+                if seen[r][c] || self.neighbors((r, c)).next().is_none() {
+                    continue;
+                }
+                // The pipes joined to this one, and whether each has both its ends joined.
+                seen[r][c] = true;
+                let mut component = vec![(r, c)];
+                let mut closed = true;
+                let mut i = 0;
+                while i < component.len() {
+                    let tile = component[i];
+                    i += 1;
+                    let connections: Vec<_> = self.connections(tile).collect();
+                    closed &= connections.len() == 2;
+                    for (nr, nc) in connections {
+                        if !std::mem::replace(&mut seen[nr][nc], true) {
+                            component.push((nr, nc));
+                        }
+                    }
+                }
+                if component.contains(&self.start) {
+                    for &tile in &component {
+                        issues.extend(self.dangling_ends(tile));
+                    }
+                } else if closed {
+                    issues.push(Issue::OtherLoop {
+                        at: (r, c),
+                        len: component.len(),
+                    });
+                }
+            }
+        }
+        issues
+    }
+
+    fn dangling_ends(&self, (r, c): (usize, usize)) -> Vec<Issue> {
+        pipe_deltas(self.mtx[r][c])
+            .iter()
+            .filter(|&(dr, dc)| {
+                let (nr, nc) = (r as i32 + dr, c as i32 + dc);
+                !self
+                    .connections((r, c))
+                    .any(|(cr, cc)| (cr as i32, cc as i32) == (nr, nc))
+            })
+            .map(|&towards| Issue::DanglingEnd {
+                at: (r, c),
+                towards,
+            })
+            .collect()
+    }
+
     fn inside(&self, (r, c): (usize, usize)) -> bool {
         if self.mtx[r][c] != '.' {
             return false;
@@ -134,32 +298,255 @@ impl Map {
     }
 }
 
-pub fn part1<R: std::io::BufRead>(reader: R) -> i64 {
-    let lines = reader.lines().map(|l| l.unwrap());
-    let mut map = Map::from_lines(lines);
+/// What to make of an `S` that more than two neighboring pipes point at.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StartShape {
+    /// Refuse the map.
+    #[default]
+    Unique,
+    /// Take the shape that closes the longest loop.
+    Largest,
+}
+
+/// Something `Map::validate` found. Coordinates are (row, column) from 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    AmbiguousStart {
+        at: (usize, usize),
+        shapes: Vec<char>,
+    },
+    /// The pipe at `at` leads by `towards` (row, column) off the map or to a tile that does
+    /// not lead back.
+    DanglingEnd {
+        at: (usize, usize),
+        towards: (i32, i32),
+    },
+    /// A closed loop of `len` pipes apart from the main loop; `at` is its top left tile.
+    OtherLoop { at: (usize, usize), len: usize },
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Issue::AmbiguousStart { at: (r, c), shapes } => write!(
+                f,
+                "start at row {}, column {} could be any of {}",
+                r + 1,
+                c + 1,
+                join(shapes)
+            ),
+            Issue::DanglingEnd {
+                at: (r, c),
+                towards,
+            } => {
+                let towards = match towards {
+                    (-1, 0) => "up",
+                    (1, 0) => "down",
+                    (0, -1) => "left",
+                    _ => "right",
+                };
+                write!(
+                    f,
+                    "pipe at row {}, column {} leads {towards} to nothing that leads back",
+                    r + 1,
+                    c + 1
+                )
+            }
+            Issue::OtherLoop { at: (r, c), len } => write!(
+                f,
+                "another loop of {len} pipes at row {}, column {}",
+                r + 1,
+                c + 1
+            ),
+        }
+    }
+}
+
+fn join(shapes: &[char]) -> String {
+    shapes
+        .iter()
+        .map(char::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// How `Map::render` draws a map.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Render {
+    /// Use ANSI colors: the loop in bold and inside tiles on a green background.
+    pub color: bool,
+    /// Draw the loop as its distances from the start, as digits 0 to 9 scaled to the
+    /// farthest tile, or as colors from blue to red with `color`.
+    pub heat_map: bool,
+}
+
+/// 256-color palette entries from near to far.
+const HEAT: [u8; 10] = [21, 27, 39, 45, 48, 46, 154, 226, 208, 196];
+
+impl Map {
+    /// Draws the map, the main loop with box drawing characters and every other tile as
+    /// inside (`▒`) or outside (blank) by the parity rule of `inside`.
+    pub fn render(&self, options: Render) -> String {
+        let mut map = self.clone();
+        map.compute_dists();
+        map.remove_non_wall();
+        let max_dist = map.dists.iter().flatten().copied().max().unwrap_or(0);
+        let mut s = String::new();
+        for r in 0..map.n_rows {
+            for c in 0..map.n_cols {
+                let dist = map.dists[r][c];
+                if dist == -1 {
+                    let inside = map.inside((r, c));
+                    match (inside, options.color) {
+                        (true, true) => s.push_str("\x1b[42m \x1b[0m"),
+                        (true, false) => s.push('▒'),
+                        (false, _) => s.push(' '),
+                    }
+                    continue;
+                }
+                let heat = (dist as usize * HEAT.len() / (max_dist as usize + 1)).min(9);
+                let pipe = match map.mtx[r][c] {
+                    '|' => '│',
+                    '-' => '─',
+                    'L' => '└',
+                    'J' => '┘',
+                    '7' => '┐',
+                    'F' => '┌',
+                    other => other,
+                };
+                match (options.heat_map, options.color) {
+                    (true, true) => {
+                        s.push_str(&format!("\x1b[1;38;5;{}m{pipe}\x1b[0m", HEAT[heat]))
+                    }
+                    (true, false) => s.push(char::from_digit(heat as u32, 10).unwrap()),
+                    (false, true) => s.push_str(&format!("\x1b[1m{pipe}\x1b[0m")),
+                    (false, false) => s.push(pipe),
+                }
+            }
+            s.push('\n');
+        }
+        s
+    }
+}
+
+/// The main loop, as the tiles on it in order from the start.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Loop {
+    pub tiles: Vec<(usize, usize)>,
+}
+
+impl Loop {
+    /// Steps from the start to the farthest tile.
+    pub fn farthest(&self) -> usize {
+        self.tiles.len() / 2
+    }
+
+    /// Twice the area of the polygon through the centers of the tiles, by the shoelace
+    /// formula.
+    pub fn twice_area(&self) -> i64 {
+        let corner = |&(r, c): &(usize, usize)| (r as i64, c as i64);
+        let next = self.tiles.iter().cycle().skip(1);
+        let sum: i64 = self
+            .tiles
+            .iter()
+            .zip(next)
+            .map(|(a, b)| {
+                let ((r1, c1), (r2, c2)) = (corner(a), corner(b));
+                r1 * c2 - r2 * c1
+            })
+            .sum();
+        sum.abs()
+    }
+
+    /// Tiles enclosed by the loop. The tiles on the loop are the polygon's boundary points,
+    /// so by Pick's theorem the area is inside + boundary / 2 - 1.
+    pub fn enclosed(&self) -> i64 {
+        let boundary = self.tiles.len() as i64;
+        (self.twice_area() - boundary + 2) / 2
+    }
+}
+
+pub fn part1<R: std::io::BufRead>(reader: R) -> common::Result<i64> {
+    part1_with(reader, StartShape::default())
+}
+
+pub fn part1_with<R: std::io::BufRead>(reader: R, choice: StartShape) -> common::Result<i64> {
+    let mut map = Map::read_with(reader, choice)?;
     map.compute_dists();
-    *map.dists
+    Ok(*map
+        .dists
         .iter()
         .map(|row| row.iter().max().unwrap())
         .max()
-        .unwrap() as i64
+        .unwrap() as i64)
+}
+
+pub fn part2<R: std::io::BufRead>(reader: R) -> common::Result<i64> {
+    part2_with(reader, StartShape::default())
+}
+
+pub fn part2_with<R: std::io::BufRead>(reader: R, choice: StartShape) -> common::Result<i64> {
+    Ok(Map::read_with(reader, choice)?.trace_loop()?.enclosed())
 }
 
-pub fn part2<R: std::io::BufRead>(reader: R) -> i64 {
-    let lines = reader.lines().map(|l| l.unwrap());
-    let mut map = Map::from_lines(lines);
+/// Part 2 by casting a ray from every tile and counting the loop crossings.
+pub fn part2_ray_cast<R: std::io::BufRead>(reader: R) -> common::Result<i64> {
+    let mut map = Map::read(reader)?;
     map.compute_dists();
     map.remove_non_wall();
     let mut inside_count: i64 = 0;
     for r in 0..map.n_rows {
         for c in 0..map.n_cols {
             if map.inside((r, c)) {
-                // println!("inside: {r} {c}");
                 inside_count += 1;
             }
         }
     }
-    inside_count
+    Ok(inside_count)
+}
+
+/// `start=largest` lets `S` take the shape that closes the longest loop when it could have
+/// several.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Start {
+    pub start: StartShape,
+}
+
+impl common::Params for Start {
+    fn set(&mut self, name: &str, value: &str) -> common::Result<()> {
+        match name {
+            "start" => {
+                self.start = match value.trim() {
+                    "unique" => StartShape::Unique,
+                    "largest" => StartShape::Largest,
+                    _ => {
+                        return Err(common::Error::Invalid(format!(
+                            "parameter {name}={value}: expected unique or largest"
+                        )))
+                    }
+                }
+            }
+            _ => return Err(common::Error::unknown_param(name)),
+        }
+        Ok(())
+    }
+}
+
+pub struct Day10;
+
+impl common::Solver for Day10 {
+    type Params1 = Start;
+    type Params2 = Start;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn part1(input: &str, params: &Start) -> common::Result<i64> {
+        part1_with(input.as_bytes(), params.start)
+    }
+
+    fn part2(input: &str, params: &Start) -> common::Result<i64> {
+        part2_with(input.as_bytes(), params.start)
+    }
 }
 
 #[cfg(test)]
@@ -181,24 +568,27 @@ LJ.LJ
 ";
 
     #[test]
-    fn test_part1_example1() {
+    fn test_part1_example1() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
-        let answer = super::part1(reader);
+        let answer = super::part1(reader)?;
         assert_eq!(answer, 4);
+        Ok(())
     }
 
     #[test]
-    fn test_part1_example2() {
+    fn test_part1_example2() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE2.as_bytes());
-        let answer = super::part1(reader);
+        let answer = super::part1(reader)?;
         assert_eq!(answer, 8);
+        Ok(())
     }
 
     #[test]
-    fn test_part1_big() {
+    fn test_part1_big() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
-        let answer = super::part1(reader);
+        let answer = super::part1(reader)?;
         assert_eq!(answer, 6640);
+        Ok(())
     }
 
     const PART2_EXAMPLES: [(i64, &str); 4] = [
@@ -263,18 +653,127 @@ L7JLJL-JLJLJL--JLJ.L
     ];
 
     #[test]
-    fn test_part2_examples() {
+    fn test_part2_examples() -> common::Result<()> {
         for (expected, input) in PART2_EXAMPLES.iter() {
             let reader = std::io::BufReader::new(input.as_bytes());
-            let answer = super::part2(reader);
+            let answer = super::part2(reader)?;
+            assert_eq!(answer, *expected, "\nexpected: {expected}\ninput:\n{input}");
+            let answer = super::part2_ray_cast(input.as_bytes())?;
             assert_eq!(answer, *expected, "\nexpected: {expected}\ninput:\n{input}");
         }
+        Ok(())
     }
 
     #[test]
-    fn test_part2_big() {
+    fn test_part2_big() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
-        let answer = super::part2(reader);
+        let answer = super::part2(reader)?;
         assert_eq!(answer, 411);
+        Ok(())
+    }
+
+    #[test]
+    fn test_render() -> common::Result<()> {
+        let map = super::Map::read(PART2_EXAMPLES[1].1.as_bytes())?;
+        let plain = map.render(Default::default()).replace(' ', ".");
+        assert_eq!(
+            plain,
+            "\
+..........
+.┌──────┐.
+.│┌────┐│.
+.││....││.
+.││....││.
+.│└─┐┌─┘│.
+.│▒▒││▒▒│.
+.└──┘└──┘.
+..........
+"
+        );
+        let options = super::Render {
+            heat_map: true,
+            ..Default::default()
+        };
+        let heat = map.render(options);
+        assert_eq!(heat.lines().nth(1), Some(" 00011223 "));
+        assert!(heat.contains('9'));
+        let options = super::Render {
+            color: true,
+            ..Default::default()
+        };
+        let color = map.render(options);
+        assert!(color.contains("\x1b[1m┌\x1b[0m"));
+        assert_eq!(color.matches("\x1b[42m").count(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate() -> common::Result<()> {
+        let input = "\
+-S-7.F7
+.|.|.LJ
+.L-J...
+";
+        let Err(err) = super::Map::read(input.as_bytes()) else {
+            panic!("expected an ambiguous start");
+        };
+        assert_eq!(
+            err.to_string(),
+            super::Issue::AmbiguousStart {
+                at: (0, 1),
+                shapes: vec!['-', '7', 'F']
+            }
+            .to_string()
+        );
+        let (map, issues) = super::Map::read_validated(input.as_bytes())?;
+        assert_eq!(map.trace_loop()?.tiles.len(), 8);
+        assert_eq!(
+            issues,
+            [
+                super::Issue::AmbiguousStart {
+                    at: (0, 1),
+                    shapes: vec!['-', '7', 'F']
+                },
+                super::Issue::OtherLoop { at: (0, 5), len: 4 },
+            ]
+        );
+
+        let map = super::Map::read("S-7\n|.|\nL--\n".as_bytes())?;
+        let issues = map.validate();
+        assert_eq!(
+            issues,
+            [
+                super::Issue::DanglingEnd {
+                    at: (1, 2),
+                    towards: (1, 0)
+                },
+                super::Issue::DanglingEnd {
+                    at: (2, 2),
+                    towards: (0, 1)
+                },
+            ]
+        );
+        assert_eq!(
+            issues[0].to_string(),
+            "pipe at row 2, column 3 leads down to nothing that leads back"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_large_loop() -> common::Result<()> {
+        // A 300x300 ring, too big for the old fixed-size grid.
+        let n = 300;
+        let mut input = format!("S{}7\n", "-".repeat(n - 2));
+        for _ in 0..n - 2 {
+            input += &format!("|{}|\n", ".".repeat(n - 2));
+        }
+        input += &format!("L{}J\n", "-".repeat(n - 2));
+        let map = super::Map::read(input.as_bytes())?;
+        let main_loop = map.trace_loop()?;
+        assert_eq!(main_loop.tiles[..3], [(0, 0), (0, 1), (0, 2)]);
+        assert_eq!(main_loop.farthest(), 2 * (n - 1));
+        assert_eq!(main_loop.enclosed(), (n as i64 - 2).pow(2));
+        Ok(())
     }
 }
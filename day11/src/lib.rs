@@ -1,61 +1,166 @@
-use std::collections::BTreeSet;
-
-pub fn solve<R: std::io::BufRead>(reader: R, factor: usize) -> i64 {
-    let map = reader
-        .lines()
-        .map(|l| l.unwrap().chars().collect::<Vec<_>>())
-        .collect::<Vec<_>>();
-    let nrows = map.len();
-    let ncols = map[0].len();
-    let mut galaxies = vec![];
-    let mut rows_with_galaxies = BTreeSet::new();
-    let mut cols_with_galaxies = BTreeSet::new();
-    for (r, row) in map.iter().enumerate() {
-        for (c, &val) in row.iter().enumerate() {
-            if val == '#' {
-                galaxies.push((r, c));
-                rows_with_galaxies.insert(r);
-                cols_with_galaxies.insert(c);
+/// Galaxies and the empty rows and columns between them, for any expansion factor.
+#[derive(Debug, Clone)]
+pub struct GalaxyMap {
+    /// (row, column), in reading order.
+    galaxies: Vec<(usize, usize)>,
+    /// The number of empty rows before each row, and one past the last.
+    empty_rows_before: Vec<usize>,
+    empty_cols_before: Vec<usize>,
+    total: TotalDistance,
+}
+
+/// The sum of the distances between all pairs of galaxies as a function of the expansion
+/// factor: `constant + slope * factor`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TotalDistance {
+    pub constant: i64,
+    pub slope: i64,
+}
+
+impl TotalDistance {
+    pub fn at(&self, factor: usize) -> i64 {
+        self.constant + self.slope * factor as i64
+    }
+}
+
+/// Prefix sums of the empty lines: entry i is the number of empty lines before line i.
+fn empty_before(len: usize, occupied: impl Iterator<Item = usize>) -> Vec<usize> {
+    let mut empty = vec![true; len];
+    for i in occupied {
+        empty[i] = false;
+    }
+    std::iter::once(0)
+        .chain(empty.iter().scan(0, |count, &e| {
+            *count += usize::from(e);
+            Some(*count)
+        }))
+        .collect()
+}
+
+/// The sum of `x_j - x_i` over all pairs i < j of sorted values.
+fn sum_of_differences(sorted: impl Iterator<Item = usize>) -> i64 {
+    let mut sum = 0;
+    let mut prefix = 0;
+    for (count, x) in (0..).zip(sorted) {
+        sum += count * x as i64 - prefix;
+        prefix += x as i64;
+    }
+    sum
+}
+
+impl GalaxyMap {
+    pub fn read<R: std::io::BufRead>(reader: R) -> common::Result<Self> {
+        let mut galaxies = vec![];
+        let (mut nrows, mut ncols) = (0, None);
+        for (i, text) in reader.lines().enumerate() {
+            let text = text?;
+            let len = text.chars().count();
+            if ncols.is_some_and(|ncols| ncols != len) {
+                let line = common::Line::new(i + 1, &text);
+                let message = format!("expected {} columns", ncols.unwrap());
+                return Err(line.error(line.text, message).into());
+            }
+            ncols = Some(len);
+            nrows = i + 1;
+            for (c, val) in text.chars().enumerate() {
+                if val == '#' {
+                    galaxies.push((i, c));
+                }
             }
         }
+        let empty_rows_before = empty_before(nrows, galaxies.iter().map(|&(r, _)| r));
+        let empty_cols_before = empty_before(ncols.unwrap_or(0), galaxies.iter().map(|&(_, c)| c));
+
+        // With factor f, a galaxy at row r is at r + (f - 1) * empty_rows_before[r], so the
+        // sum of row distances is the sum for the rows plus f - 1 times the sum for the
+        // empty-row counts, both taken in sorted order. Galaxies are read row by row.
+        let mut cols: Vec<usize> = galaxies.iter().map(|&(_, c)| c).collect();
+        cols.sort_unstable();
+        let plain = sum_of_differences(galaxies.iter().map(|&(r, _)| r))
+            + sum_of_differences(cols.iter().copied());
+        let empty = sum_of_differences(galaxies.iter().map(|&(r, _)| empty_rows_before[r]))
+            + sum_of_differences(cols.iter().map(|&c| empty_cols_before[c]));
+        let total = TotalDistance {
+            constant: plain - empty,
+            slope: empty,
+        };
+
+        Ok(Self {
+            galaxies,
+            empty_rows_before,
+            empty_cols_before,
+            total,
+        })
     }
-    let rows_without_galaxies = (0..nrows)
-        .collect::<BTreeSet<_>>()
-        .difference(&rows_with_galaxies)
-        .cloned()
-        .collect::<BTreeSet<_>>();
-    let cols_without_galaxies = (0..ncols)
-        .collect::<BTreeSet<_>>()
-        .difference(&cols_with_galaxies)
-        .cloned()
-        .collect::<BTreeSet<_>>();
-
-    let mut answer = 0;
-    for (i1, &galaxy1) in galaxies.iter().enumerate() {
-        for &galaxy2 in galaxies.iter().skip(i1 + 1) {
-            let (r1, c1) = galaxy1;
-            let (r2, c2) = galaxy2;
-            let (r1, r2) = (r1.min(r2), r1.max(r2));
-            let (c1, c2) = (c1.min(c2), c1.max(c2));
-            let dr = r1.abs_diff(r2);
-            let dc = c1.abs_diff(c2);
-            let expdr = dr + (factor - 1) * rows_without_galaxies.range(r1..r2).count();
-            let expdc = dc + (factor - 1) * cols_without_galaxies.range(c1..c2).count();
-            answer += expdr + expdc;
-        }
+
+    pub fn galaxies(&self) -> &[(usize, usize)] {
+        &self.galaxies
+    }
+
+    /// The distance between galaxies `a` and `b`, numbered from 0 in reading order. Like
+    /// `TotalDistance::at`, a factor of 0 makes the empty lines disappear.
+    pub fn distance(&self, a: usize, b: usize, factor: usize) -> i64 {
+        let ((r1, c1), (r2, c2)) = (self.galaxies[a], self.galaxies[b]);
+        let along = |x1: usize, x2: usize, empty_before: &[usize]| {
+            let (x1, x2) = (x1.min(x2), x1.max(x2));
+            let empty = (empty_before[x2] - empty_before[x1]) as i64;
+            (x2 - x1) as i64 + (factor as i64 - 1) * empty
+        };
+        along(r1, r2, &self.empty_rows_before) + along(c1, c2, &self.empty_cols_before)
+    }
+
+    pub fn total(&self) -> TotalDistance {
+        self.total
     }
+}
 
-    answer as i64
+/// The sum of the distances between all pairs of galaxies, each empty line counting as
+/// `factor` lines; with 0 they disappear.
+pub fn solve<R: std::io::BufRead>(reader: R, factor: usize) -> common::Result<i64> {
+    Ok(GalaxyMap::read(reader)?.total().at(factor))
 }
 
-pub fn part1<R: std::io::BufRead>(reader: R) -> i64 {
+pub fn part1<R: std::io::BufRead>(reader: R) -> common::Result<i64> {
     solve(reader, 2)
 }
 
-pub fn part2<R: std::io::BufRead>(reader: R) -> i64 {
+pub fn part2<R: std::io::BufRead>(reader: R) -> common::Result<i64> {
     solve(reader, 1_000_000)
 }
 
+/// Expansion factor for empty rows and columns; 2 for part 1 and 1_000_000 for part 2 unless set.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Expansion {
+    pub factor: Option<usize>,
+}
+
+impl common::Params for Expansion {
+    fn set(&mut self, name: &str, value: &str) -> common::Result<()> {
+        match name {
+            "factor" => self.factor = Some(common::parse_param(name, value)?),
+            _ => return Err(common::Error::unknown_param(name)),
+        }
+        Ok(())
+    }
+}
+
+pub struct Day11;
+
+impl common::Solver for Day11 {
+    type Params1 = Expansion;
+    type Params2 = Expansion;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn part1(input: &str, params: &Expansion) -> common::Result<i64> {
+        solve(input.as_bytes(), params.factor.unwrap_or(2))
+    }
+
+    fn part2(input: &str, params: &Expansion) -> common::Result<i64> {
+        solve(input.as_bytes(), params.factor.unwrap_or(1_000_000))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     const EXAMPLE1: &str = "\
@@ -72,37 +177,64 @@ mod tests {
 ";
 
     #[test]
-    fn part1_example() {
+    fn part1_example() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
-        let result = super::part1(reader);
+        let result = super::part1(reader)?;
         assert_eq!(result, 374);
+        Ok(())
+    }
+
+    #[test]
+    fn galaxy_map() -> common::Result<()> {
+        let map = super::GalaxyMap::read(EXAMPLE1.as_bytes())?;
+        assert_eq!(map.galaxies().len(), 9);
+        let pairs = [(4, 8), (0, 6), (2, 5), (7, 8)];
+        assert_eq!(pairs.map(|(a, b)| map.distance(a, b, 2)), [9, 15, 17, 5]);
+        assert_eq!(map.total().at(2), 374);
+
+        assert_eq!(map.distance(0, 6, 0), 9);
+        for factor in [0, 1, 100] {
+            let mut total = 0;
+            for a in 0..9 {
+                for b in a + 1..9 {
+                    total += map.distance(b, a, factor);
+                }
+            }
+            assert_eq!(total, map.total().at(factor));
+        }
+        assert_eq!(super::solve(EXAMPLE1.as_bytes(), 0)?, map.total().at(0));
+        Ok(())
     }
 
     #[test]
-    fn part1_big() {
+    fn part1_big() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
-        let result = super::part1(reader);
+        let result = super::part1(reader)?;
         assert_eq!(result, 9536038);
+        Ok(())
     }
 
     #[test]
-    fn part2_example() {
+    fn part2_example() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
-        let result = super::solve(reader, 10);
+        let result = super::solve(reader, 10)?;
         assert_eq!(result, 1030);
+        Ok(())
     }
 
     #[test]
-    fn part2_example2() {
+    fn part2_example2() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
-        let result = super::solve(reader, 100);
+        let result = super::solve(reader, 100)?;
         assert_eq!(result, 8410);
+        Ok(())
     }
 
     #[test]
-    fn part2_big() {
+    fn part2_big() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
-        let result = super::part2(reader);
+        let result = super::part2(reader)?;
         assert_eq!(result, 447744640566);
+        Ok(())
     }
 }
@@ -18,98 +18,333 @@ fn compatible(states: &[char], group_size: usize, position: usize) -> bool {
     true
 }
 
-struct ArrangeCounter<'a> {
-    memo: std::collections::HashMap<(&'a [char], &'a [usize]), usize>,
+/// A count of arrangements; `f64` does when only whether there are any matters, since it
+/// cannot overflow.
+pub trait Count: Clone + num::Zero + num::One + for<'a> std::ops::AddAssign<&'a Self> {}
+
+impl<N: Clone + num::Zero + num::One + for<'a> std::ops::AddAssign<&'a N>> Count for N {}
+
+/// The number of arrangements of every suffix of the states with every suffix of the groups,
+/// filled in from the end.
+struct Ways<N> {
+    width: usize,
+    table: Vec<N>,
 }
 
-impl<'a> ArrangeCounter<'a> {
-    fn new() -> Self {
-        ArrangeCounter {
-            memo: std::collections::HashMap::new(),
+impl<N: Count> Ways<N> {
+    fn new(states: &[char], groups: &[usize]) -> Self {
+        let n = states.len();
+        let width = groups.len() + 1;
+        // The number of states from each position on that could be damaged, up to the next
+        // operational one.
+        let mut run = vec![0; n + 1];
+        for p in (0..n).rev() {
+            run[p] = if states[p] == '.' { 0 } else { run[p + 1] + 1 };
         }
+        let mut table = vec![N::zero(); (n + 1) * width];
+        table[n * width + groups.len()] = N::one();
+        for p in (0..n).rev() {
+            for g in 0..width {
+                let mut ways = N::zero();
+                if states[p] != '#' {
+                    ways += &table[(p + 1) * width + g];
+                }
+                if let Some(&size) = groups.get(g) {
+                    if size > 0 && run[p] >= size && (p + size == n || states[p + size] != '#') {
+                        let next = (p + size + 1).min(n);
+                        ways += &table[next * width + g + 1];
+                    }
+                }
+                table[p * width + g] = ways;
+            }
+        }
+        Ways { width, table }
     }
 
-    fn _count_arrangements(&mut self, states: &'a [char], groups: &'a [usize]) -> usize {
-        if groups.is_empty() {
-            return if states.iter().all(|&c| c == '.' || c == '?') {
-                1
-            } else {
-                0
-            };
-        }
+    /// The arrangements of `states[position..]` with `groups[group..]`.
+    fn at(&self, position: usize, group: usize) -> &N {
+        &self.table[position * self.width + group]
+    }
+}
+
+fn possible(states: &[char], groups: &[usize]) -> bool {
+    *Ways::<f64>::new(states, groups).at(0, 0) > 0.0
+}
+
+/// The number of ways to fill in the `?` states so that the damaged springs form `groups`.
+pub fn count_arrangements<N: Count>(states: &[char], groups: &[usize]) -> N {
+    Ways::<N>::new(states, groups).at(0, 0).clone()
+}
+
+/// Every way to fill in the `?` states, generated one at a time. Branches without any
+/// arrangement are skipped by counting them first, so each step is cheap even when there
+/// are many arrangements.
+pub fn arrangements<'a>(states: &'a [char], groups: &'a [usize]) -> Arrangements<'a> {
+    let ways = Ways::new(states, groups);
+    let stack = if *ways.at(0, 0) > 0.0 {
+        vec![Frame { start: 0, next: 0 }]
+    } else {
+        vec![]
+    };
+    Arrangements {
+        ways,
+        states,
+        groups,
+        stack,
+        row: vec![],
+    }
+}
+
+/// Where the search for one group's position is: the states before `start` are decided,
+/// and `next` is the next offset from `start` to try.
+struct Frame {
+    start: usize,
+    next: usize,
+}
+
+pub struct Arrangements<'a> {
+    ways: Ways<f64>,
+    states: &'a [char],
+    groups: &'a [usize],
+    /// One frame per group placed so far, plus the one being placed.
+    stack: Vec<Frame>,
+    row: Vec<char>,
+}
 
-        let group_size = groups[0];
-        let mut count = 0;
-        for position in 0..states.len() {
-            if compatible(states, group_size, position) {
-                count += self.count_arrangements(
-                    &states[(position + group_size + 1).min(states.len())..],
-                    &groups[1..],
-                );
+impl Iterator for Arrangements<'_> {
+    type Item = Vec<char>;
+
+    fn next(&mut self) -> Option<Vec<char>> {
+        while !self.stack.is_empty() {
+            let group = self.stack.len() - 1;
+            let frame = &mut self.stack[group];
+            self.row.truncate(frame.start);
+            if group == self.groups.len() {
+                let mut row = self.row.clone();
+                row.resize(self.states.len(), '.');
+                self.stack.pop();
+                return Some(row);
+            }
+            let rest = &self.states[frame.start..];
+            let size = self.groups[group];
+            let mut found = None;
+            while frame.next < rest.len() {
+                let position = frame.next;
+                frame.next += 1;
+                if !compatible(rest, size, position) {
+                    continue;
+                }
+                let after = (position + size + 1).min(rest.len());
+                if *self.ways.at(frame.start + after, group + 1) > 0.0 {
+                    found = Some((position, after));
+                    break;
+                }
             }
+            match found {
+                Some((position, after)) => {
+                    let start = frame.start + after;
+                    self.row.extend(std::iter::repeat_n('.', position));
+                    self.row.extend(std::iter::repeat_n('#', size));
+                    self.row
+                        .extend(std::iter::repeat_n('.', after - position - size));
+                    self.stack.push(Frame { start, next: 0 });
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+        None
+    }
+}
+
+/// `states` with every `?` that is the same in all arrangements filled in, or `None` if
+/// there are no arrangements.
+pub fn forced(states: &[char], groups: &[usize]) -> Option<Vec<char>> {
+    if !possible(states, groups) {
+        return None;
+    }
+    let mut result = states.to_vec();
+    let mut trial = states.to_vec();
+    for (i, &state) in states.iter().enumerate() {
+        if state != '?' {
+            continue;
+        }
+        trial[i] = '#';
+        let damaged = possible(&trial, groups);
+        trial[i] = '.';
+        let operational = possible(&trial, groups);
+        trial[i] = '?';
+        match (damaged, operational) {
+            (true, false) => result[i] = '#',
+            (false, true) => result[i] = '.',
+            _ => {}
         }
+    }
+    Some(result)
+}
+
+/// A 2D puzzle where every row and column is a spring record: `rows[r]` are the group sizes
+/// of row r read left to right and `cols[c]` those of column c read top to bottom.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Nonogram {
+    pub rows: Vec<Vec<usize>>,
+    pub cols: Vec<Vec<usize>>,
+}
 
-        count
+impl Nonogram {
+    /// A grid of `#` and `.` that matches every clue, if there is one.
+    pub fn solve(&self) -> Option<Vec<Vec<char>>> {
+        self.solve_from(vec![vec!['?'; self.cols.len()]; self.rows.len()])
     }
 
-    fn count_arrangements(&mut self, states: &'a [char], groups: &'a [usize]) -> usize {
-        if let Some(result) = self.memo.get(&(states, groups)) {
-            *result
-        } else {
-            let result = self._count_arrangements(states, groups);
-            self.memo.insert((states, groups), result);
-            result
+    /// Fills in the cells forced by single rows and columns until nothing changes, then
+    /// guesses the first cell left.
+    fn solve_from(&self, mut grid: Vec<Vec<char>>) -> Option<Vec<Vec<char>>> {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (row, groups) in grid.iter_mut().zip(&self.rows) {
+                let line = forced(row, groups)?;
+                changed |= line != *row;
+                *row = line;
+            }
+            for (c, groups) in self.cols.iter().enumerate() {
+                let col: Vec<char> = grid.iter().map(|row| row[c]).collect();
+                let line = forced(&col, groups)?;
+                changed |= line != col;
+                for (row, state) in grid.iter_mut().zip(line) {
+                    row[c] = state;
+                }
+            }
         }
+        let unknown = grid
+            .iter()
+            .enumerate()
+            .find_map(|(r, row)| Some((r, row.iter().position(|&s| s == '?')?)));
+        let Some((r, c)) = unknown else {
+            return Some(grid);
+        };
+        ['#', '.'].into_iter().find_map(|guess| {
+            let mut grid = grid.clone();
+            grid[r][c] = guess;
+            self.solve_from(grid)
+        })
     }
 }
 
-pub fn part1<R: std::io::BufRead>(reader: R) -> usize {
-    let mut total = 0;
-    for line in reader.lines() {
-        let line = line.unwrap();
-        let (states, groups) = line.trim().split_once(' ').unwrap();
+/// One line of the condition records: the spring states and the damaged group sizes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub states: Vec<char>,
+    pub groups: Vec<usize>,
+}
+
+impl Record {
+    /// Parses `???.### 1,1,3`.
+    fn parse(line: &common::Line) -> common::Result<Self> {
+        let (states, groups) = line.split_once(line.text, " ")?;
+        if let Some((i, c)) = states.char_indices().find(|&(_, c)| !".#?".contains(c)) {
+            let at = &states[i..i + c.len_utf8()];
+            return Err(line.error(at, "unknown spring state").into());
+        }
         let states = states.chars().collect::<Vec<_>>();
         let groups = groups
             .split(',')
-            .map(|s| s.parse::<usize>().unwrap())
-            .collect::<Vec<_>>();
+            .map(|s| line.parse::<usize>(s))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Record { states, groups })
+    }
 
-        let count = ArrangeCounter::new().count_arrangements(&states[..], &groups[..]);
-        total += count;
+    /// `factor` copies of the states joined by `?`, and `factor` copies of the groups.
+    pub fn unfold(&self, factor: usize) -> Record {
+        let mut states = vec![];
+        for i in 0..factor {
+            if i > 0 {
+                states.push('?');
+            }
+            states.extend(&self.states);
+        }
+        Record {
+            states,
+            groups: self.groups.repeat(factor),
+        }
+    }
 
-        // println!("{states:?} {groups:?} => {count}");
+    pub fn count<N: Count>(&self) -> N {
+        count_arrangements(&self.states, &self.groups)
     }
-    total
 }
 
-pub fn part2<R: std::io::BufRead>(reader: R) -> usize {
-    let mut total = 0;
-    for line in reader.lines() {
-        let line = line.unwrap();
-        let (states, groups) = line.trim().split_once(' ').unwrap();
-        let states = states.chars().collect::<Vec<_>>();
-        let groups = groups
-            .split(',')
-            .map(|s| s.parse::<usize>().unwrap())
-            .collect::<Vec<_>>();
+pub fn read_records<R: std::io::BufRead>(reader: R) -> common::Result<Vec<Record>> {
+    let mut records = vec![];
+    for (i, line) in reader.lines().enumerate() {
+        records.push(Record::parse(&common::Line::new(i + 1, line?.trim()))?);
+    }
+    Ok(records)
+}
+
+/// The number of arrangements of each line, unfolded `factor` times.
+pub fn line_counts<N: Count, R: std::io::BufRead>(
+    reader: R,
+    factor: usize,
+) -> common::Result<Vec<N>> {
+    Ok(read_records(reader)?
+        .iter()
+        .map(|record| record.unfold(factor).count())
+        .collect())
+}
+
+pub fn part1<R: std::io::BufRead>(reader: R) -> common::Result<u128> {
+    Ok(line_counts::<u128, _>(reader, 1)?.into_iter().sum())
+}
+
+pub fn part2<R: std::io::BufRead>(reader: R) -> common::Result<num::BigUint> {
+    part2_with(reader, 5)
+}
 
-        let states = (0..5)
-            .flat_map(|i| {
-                std::iter::repeat('?')
-                    .take(if i == 0 { 0 } else { 1 })
-                    .chain(states.iter().cloned())
-            })
-            .collect::<Vec<_>>();
-        let groups = (0..5)
-            .flat_map(|_| groups.iter().cloned())
-            .collect::<Vec<_>>();
+pub fn part2_with<R: std::io::BufRead>(reader: R, factor: usize) -> common::Result<num::BigUint> {
+    if factor == 0 {
+        return Err(common::Error::Invalid(
+            "unfold factor must be positive".into(),
+        ));
+    }
+    Ok(line_counts::<num::BigUint, _>(reader, factor)?
+        .into_iter()
+        .sum())
+}
 
-        let count = ArrangeCounter::new().count_arrangements(&states[..], &groups[..]);
-        total += count;
+/// How many times part 2 unfolds each record; 5 unless set.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Unfold {
+    pub factor: Option<usize>,
+}
 
-        // println!("{states:?} {groups:?} => {count}");
+impl common::Params for Unfold {
+    fn set(&mut self, name: &str, value: &str) -> common::Result<()> {
+        match name {
+            "unfold" => self.factor = Some(common::parse_param(name, value)?),
+            _ => return Err(common::Error::unknown_param(name)),
+        }
+        Ok(())
+    }
+}
+
+pub struct Day12;
+
+impl common::Solver for Day12 {
+    type Params1 = ();
+    type Params2 = Unfold;
+    type Answer1 = u128;
+    type Answer2 = num::BigUint;
+
+    fn part1(input: &str, _: &()) -> common::Result<u128> {
+        part1(input.as_bytes())
+    }
+
+    fn part2(input: &str, params: &Unfold) -> common::Result<num::BigUint> {
+        part2_with(input.as_bytes(), params.factor.unwrap_or(5))
     }
-    total
 }
 
 #[cfg(test)]
@@ -124,30 +359,98 @@ mod tests {
 ";
 
     #[test]
-    fn part1_example() {
+    fn part1_example() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
-        let result = super::part1(reader);
+        let result = super::part1(reader)?;
         assert_eq!(result, 21);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_errors() {
+        let Err(common::Error::Parse(err)) = super::part1("?.# 1\n?é# 1\n".as_bytes()) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 2, "é"));
+    }
+
+    #[test]
+    fn arrangements_and_forced() {
+        let states: Vec<char> = "?###????????".chars().collect();
+        let all: Vec<String> = super::arrangements(&states, &[3, 2, 1])
+            .map(|row| row.into_iter().collect())
+            .collect();
+        assert_eq!(all.len(), 10);
+        assert_eq!(all[0], ".###.##.#...");
+        assert_eq!(all[9], ".###....##.#");
+        let unique: std::collections::BTreeSet<_> = all.iter().collect();
+        assert_eq!(unique.len(), 10);
+
+        let states: Vec<char> = ".??..??...?##.".chars().collect();
+        let forced: String = super::forced(&states, &[1, 1, 3])
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(forced, ".??..??...###.");
+        assert_eq!(super::forced(&states, &[4]), None);
+        assert_eq!(super::arrangements(&states, &[4]).count(), 0);
+    }
+
+    #[test]
+    fn line_counts_unfolded() -> common::Result<()> {
+        let counts = super::line_counts::<u128, _>(EXAMPLE1.as_bytes(), 5)?;
+        assert_eq!(counts, [1, 16384, 1, 16, 2500, 506250]);
+
+        // 249 unknown springs with 50 groups of one: 200 choose 50 arrangements, more than
+        // a u128 holds.
+        let record = super::Record {
+            states: vec!['?'; 4],
+            groups: vec![1],
+        }
+        .unfold(50);
+        let count: num::BigUint = record.count();
+        let expected = num::integer::binomial(num::BigUint::from(200u32), 50u32.into());
+        assert_eq!(count, expected);
+        assert!(count > u128::MAX.into());
+        Ok(())
+    }
+
+    #[test]
+    fn nonogram() {
+        let puzzle = super::Nonogram {
+            rows: vec![vec![3], vec![1, 1], vec![5], vec![1, 1], vec![1, 1]],
+            cols: vec![vec![4], vec![1, 1], vec![1, 1], vec![1, 1], vec![4]],
+        };
+        let solution: Vec<String> = puzzle
+            .solve()
+            .unwrap()
+            .into_iter()
+            .map(|row| row.into_iter().collect())
+            .collect();
+        assert_eq!(solution, [".###.", "#...#", "#####", "#...#", "#...#"]);
     }
 
     #[test]
-    fn part1_big() {
+    fn part1_big() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
-        let result = super::part1(reader);
+        let result = super::part1(reader)?;
         assert_eq!(result, 7110);
+        Ok(())
     }
 
     #[test]
-    fn part2_example() {
+    fn part2_example() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
-        let result = super::part2(reader);
-        assert_eq!(result, 525152);
+        let result = super::part2(reader)?;
+        assert_eq!(result, 525152u32.into());
+        Ok(())
     }
 
     #[test]
-    fn part2_big() {
+    fn part2_big() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
-        let result = super::part2(reader);
-        assert_eq!(result, 1566786613613);
+        let result = super::part2(reader)?;
+        assert_eq!(result, 1566786613613u64.into());
+        Ok(())
     }
 }
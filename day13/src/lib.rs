@@ -1,12 +1,14 @@
 use ndarray::s;
 
-fn parse<R: std::io::BufRead>(mut reader: R) -> Vec<ndarray::Array2<u8>> {
+fn parse<R: std::io::BufRead>(mut reader: R) -> common::Result<Vec<ndarray::Array2<u8>>> {
     let mut maps = vec![];
+    let mut line_number = 0;
     'outer: loop {
         let mut map = ndarray::Array2::zeros((0, 0));
         loop {
             let mut line = String::new();
-            reader.read_line(&mut line).unwrap();
+            reader.read_line(&mut line)?;
+            line_number += 1;
             // println!("line: {line:#?}");
             if line.is_empty() {
                 maps.push(map);
@@ -16,18 +18,23 @@ fn parse<R: std::io::BufRead>(mut reader: R) -> Vec<ndarray::Array2<u8>> {
                 maps.push(map);
                 break;
             }
-            let line = line
-                .trim()
+            let text = common::Line::new(line_number, line.trim());
+            let line = text
+                .text
                 .chars()
                 .map(|c| (c == '#') as u8)
                 .collect::<ndarray::Array1<u8>>();
+            if map.nrows() == 0 {
+                map = ndarray::Array2::zeros((0, line.len()));
+            }
             if line.len() != map.ncols() {
-                map = map.into_shape((0, line.len())).unwrap();
+                let message = format!("expected {} columns", map.ncols());
+                return Err(text.error(text.text, message).into());
             }
             map.push_row(line.view()).unwrap();
         }
     }
-    maps
+    Ok(maps)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -73,8 +80,8 @@ fn find_reflections(map: ndarray::ArrayView2<u8>) -> impl Iterator<Item = Refl>
         }))
 }
 
-pub fn part1<R: std::io::BufRead>(reader: R) -> i64 {
-    let maps = parse(reader);
+pub fn part1<R: std::io::BufRead>(reader: R) -> common::Result<i64> {
+    let maps = parse(reader)?;
     let mut result = 0;
 
     for map in maps {
@@ -85,11 +92,11 @@ pub fn part1<R: std::io::BufRead>(reader: R) -> i64 {
         result += acc;
     }
 
-    result as i64
+    Ok(result as i64)
 }
 
-pub fn part2<R: std::io::BufRead>(reader: R) -> i64 {
-    let maps = parse(reader);
+pub fn part2<R: std::io::BufRead>(reader: R) -> common::Result<i64> {
+    let maps = parse(reader)?;
     let mut result = 0;
 
     for map in maps {
@@ -109,7 +116,24 @@ pub fn part2<R: std::io::BufRead>(reader: R) -> i64 {
         }
     }
 
-    result as i64
+    Ok(result as i64)
+}
+
+pub struct Day13;
+
+impl common::Solver for Day13 {
+    type Params1 = ();
+    type Params2 = ();
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn part1(input: &str, _: &()) -> common::Result<i64> {
+        part1(input.as_bytes())
+    }
+
+    fn part2(input: &str, _: &()) -> common::Result<i64> {
+        part2(input.as_bytes())
+    }
 }
 
 #[cfg(test)]
@@ -133,30 +157,34 @@ mod tests {
 ";
 
     #[test]
-    fn part1_example() {
+    fn part1_example() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
-        let result = super::part1(reader);
+        let result = super::part1(reader)?;
         assert_eq!(result, 405);
+        Ok(())
     }
 
     #[test]
-    fn part1_big() {
+    fn part1_big() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
-        let result = super::part1(reader);
+        let result = super::part1(reader)?;
         assert_eq!(result, 34772);
+        Ok(())
     }
 
     #[test]
-    fn part2_example() {
+    fn part2_example() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
-        let result = super::part2(reader);
+        let result = super::part2(reader)?;
         assert_eq!(result, 400);
+        Ok(())
     }
 
     #[test]
-    fn part2_big() {
+    fn part2_big() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
-        let result = super::part2(reader);
+        let result = super::part2(reader)?;
         assert_eq!(result, 35554);
+        Ok(())
     }
 }
@@ -1,20 +1,31 @@
 use ndarray::s;
 
-fn parse<R: std::io::BufRead>(reader: R) -> ndarray::Array2<u8> {
+fn parse<R: std::io::BufRead>(reader: R) -> common::Result<ndarray::Array2<u8>> {
     let mut map = ndarray::Array2::<u8>::zeros((0, 0));
-    for line in reader.lines() {
-        let line = line
-            .unwrap()
-            .trim()
+    for (i, text) in reader.lines().enumerate() {
+        let text = text?;
+        let text = common::Line::new(i + 1, text.trim());
+        if let Some((at, c)) = text.text.char_indices().find(|&(_, c)| !"O#.".contains(c)) {
+            let message = "unknown tile";
+            return Err(text
+                .error(&text.text[at..at + c.len_utf8()], message)
+                .into());
+        }
+        let line = text
+            .text
             .chars()
             .map(|c| c as u8)
             .collect::<ndarray::Array1<_>>();
+        if map.nrows() == 0 {
+            map = ndarray::Array2::zeros((0, line.len()));
+        }
         if line.len() != map.ncols() {
-            map = map.into_shape((0, line.len())).unwrap();
+            let message = format!("expected {} columns", map.ncols());
+            return Err(text.error(text.text, message).into());
         }
         map.push_row(line.view()).unwrap();
     }
-    map
+    Ok(map)
 }
 
 fn tilt_up(map: &mut ndarray::Array2<u8>) {
@@ -44,10 +55,10 @@ fn load(map: &ndarray::Array2<u8>) -> usize {
     result
 }
 
-pub fn part1<R: std::io::BufRead>(reader: R) -> usize {
-    let mut map = parse(reader);
+pub fn part1<R: std::io::BufRead>(reader: R) -> common::Result<usize> {
+    let mut map = parse(reader)?;
     tilt_up(&mut map);
-    load(&map)
+    Ok(load(&map))
 }
 
 fn rotate_clockwise(map: &mut ndarray::Array2<u8>) {
@@ -56,8 +67,8 @@ fn rotate_clockwise(map: &mut ndarray::Array2<u8>) {
     *map = map2.into_owned();
 }
 
-pub fn part2<R: std::io::BufRead>(reader: R) -> usize {
-    let mut map = parse(reader);
+pub fn part2<R: std::io::BufRead>(reader: R) -> common::Result<usize> {
+    let mut map = parse(reader)?;
 
     let mut cycle_count: usize = 0;
     for _ in 0..1000 {
@@ -91,7 +102,24 @@ pub fn part2<R: std::io::BufRead>(reader: R) -> usize {
         }
     }
 
-    load(&map)
+    Ok(load(&map))
+}
+
+pub struct Day14;
+
+impl common::Solver for Day14 {
+    type Params1 = ();
+    type Params2 = ();
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn part1(input: &str, _: &()) -> common::Result<usize> {
+        part1(input.as_bytes())
+    }
+
+    fn part2(input: &str, _: &()) -> common::Result<usize> {
+        part2(input.as_bytes())
+    }
 }
 
 #[cfg(test)]
@@ -110,30 +138,44 @@ O.#..O.#.#
 ";
 
     #[test]
-    fn part1_example() {
+    fn part1_example() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
-        let result = super::part1(reader);
+        let result = super::part1(reader)?;
         assert_eq!(result, 136);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_errors() {
+        for (input, line) in [("é", 1), ("###\n#é#", 2)] {
+            let Err(common::Error::Parse(err)) = super::part1(input.as_bytes()) else {
+                panic!("expected a parse error");
+            };
+            assert_eq!((err.line, err.text.as_str()), (line, "é"));
+        }
     }
 
     #[test]
-    fn part1_big() {
+    fn part1_big() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
-        let result = super::part1(reader);
+        let result = super::part1(reader)?;
         assert_eq!(result, 113424);
+        Ok(())
     }
 
     #[test]
-    fn part2_example() {
+    fn part2_example() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
-        let result = super::part2(reader);
+        let result = super::part2(reader)?;
         assert_eq!(result, 64);
+        Ok(())
     }
 
     #[test]
-    fn part2_big() {
+    fn part2_big() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
-        let result = super::part2(reader);
+        let result = super::part2(reader)?;
         assert_eq!(result, 96003);
+        Ok(())
     }
 }
@@ -7,7 +7,7 @@ fn hash(buf: &str) -> u8 {
     h
 }
 
-pub fn part1<R: std::io::BufRead>(mut reader: R) -> anyhow::Result<usize> {
+pub fn part1<R: std::io::BufRead>(mut reader: R) -> common::Result<usize> {
     let mut input = String::new();
     reader.read_to_string(&mut input)?;
     let input = input.replace('\n', "");
@@ -40,25 +40,18 @@ impl<'a> Command<'a> {
         }
     }
 
-    fn from_str(s: &'a str) -> anyhow::Result<Self> {
+    fn parse(line: &common::Line, s: &'a str) -> Result<Self, common::ParseError> {
         let pat = ['=', '-'];
         let (label, focal_length) = s
             .split_once(pat)
-            .ok_or(anyhow::anyhow!("= or - not found"))?;
-        let op = s
-            .matches(pat)
-            .next()
-            .ok_or(anyhow::anyhow!("pat not found"))?
-            .chars()
-            .next()
-            .ok_or(anyhow::anyhow!("pat not found"))?;
-        match op {
-            '=' => Ok(Self::AddOrReplace {
+            .ok_or_else(|| line.error(s, "expected '=' or '-'"))?;
+        if s[label.len()..].starts_with('=') {
+            Ok(Self::AddOrReplace {
                 label,
-                focal_length: focal_length.parse::<usize>()?,
-            }),
-            '-' => Ok(Self::Remove { label }),
-            _ => anyhow::bail!("invalid op: {op}"),
+                focal_length: line.parse::<usize>(focal_length)?,
+            })
+        } else {
+            Ok(Self::Remove { label })
         }
     }
 }
@@ -71,7 +64,7 @@ impl LensBox {
         }
     }
 
-    fn interpret(&mut self, cmd: &Command) -> anyhow::Result<()> {
+    fn interpret(&mut self, cmd: &Command) {
         match *cmd {
             Command::AddOrReplace {
                 label,
@@ -97,7 +90,6 @@ impl LensBox {
                 }
             }
         }
-        Ok(())
     }
 
     fn focusing_power(&self) -> usize {
@@ -116,23 +108,14 @@ struct LensBoxSet {
 }
 
 impl LensBoxSet {
-    fn new() -> anyhow::Result<Self> {
-        Ok(Self {
-            boxes: (0..256)
-                .map(LensBox::new)
-                .collect::<Vec<_>>()
-                .try_into()
-                .map_err(|_| anyhow::anyhow!("failed to convert vec to array"))?,
-        })
-    }
-
-    fn interpret(&mut self, cmd: &str) -> anyhow::Result<()> {
-        let cmd = Command::from_str(cmd)?;
-        self.boxes
-            .get_mut(hash(cmd.label()) as usize)
-            .ok_or(anyhow::anyhow!("out of bounds?"))?
-            .interpret(&cmd)?;
-        Ok(())
+    fn new() -> Self {
+        Self {
+            boxes: std::array::from_fn(LensBox::new),
+        }
+    }
+
+    fn interpret(&mut self, cmd: &Command) {
+        self.boxes[hash(cmd.label()) as usize].interpret(cmd);
     }
 
     fn focusing_power(&self) -> usize {
@@ -140,20 +123,35 @@ impl LensBoxSet {
     }
 }
 
-pub fn part2<R: std::io::BufRead>(mut reader: R) -> anyhow::Result<usize> {
+pub fn part2<R: std::io::BufRead>(mut reader: R) -> common::Result<usize> {
     let mut input = String::new();
     reader.read_to_string(&mut input)?;
     let input = input.trim().replace('\n', "");
-    let lens_box_set =
-        input
-            .split(',')
-            .try_fold(LensBoxSet::new()?, |mut acc, cmd| -> anyhow::Result<_> {
-                acc.interpret(cmd)?;
-                Ok(acc)
-            })?;
+    let line = common::Line::new(1, &input);
+    let mut lens_box_set = LensBoxSet::new();
+    for cmd in input.split(',') {
+        lens_box_set.interpret(&Command::parse(&line, cmd)?);
+    }
     Ok(lens_box_set.focusing_power())
 }
 
+pub struct Day15;
+
+impl common::Solver for Day15 {
+    type Params1 = ();
+    type Params2 = ();
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn part1(input: &str, _: &()) -> common::Result<usize> {
+        part1(input.as_bytes())
+    }
+
+    fn part2(input: &str, _: &()) -> common::Result<usize> {
+        part2(input.as_bytes())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -166,7 +164,7 @@ rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7
 ";
 
     #[test]
-    fn part1_example() -> anyhow::Result<()> {
+    fn part1_example() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
         let result = super::part1(reader)?;
         assert_eq!(result, 1320);
@@ -174,7 +172,7 @@ rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7
     }
 
     #[test]
-    fn part1_big() -> anyhow::Result<()> {
+    fn part1_big() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
         let result = super::part1(reader)?;
         assert_eq!(result, 505427);
@@ -182,7 +180,7 @@ rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7
     }
 
     #[test]
-    fn part2_example() -> anyhow::Result<()> {
+    fn part2_example() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
         let result = super::part2(reader)?;
         assert_eq!(result, 145);
@@ -190,10 +188,19 @@ rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7
     }
 
     #[test]
-    fn part2_big() -> anyhow::Result<()> {
+    fn part2_big() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
         let result = super::part2(reader)?;
         assert_eq!(result, 243747);
         Ok(())
     }
+
+    #[test]
+    fn part2_malformed() {
+        let reader = std::io::BufReader::new("rn=1,cm-,qp=x".as_bytes());
+        let Err(common::Error::Parse(err)) = super::part2(reader) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!((err.line, err.column, err.text.as_str()), (1, 13, "x"));
+    }
 }
@@ -55,27 +55,44 @@ struct Map {
 }
 
 impl Map {
-    fn read<R: std::io::BufRead>(reader: R) -> anyhow::Result<Self> {
+    fn read<R: std::io::BufRead>(reader: R) -> common::Result<Self> {
         let (mut nrows, mut ncols) = (0, 0);
         let mut vals = vec![];
-        for (r, line) in reader.lines().enumerate() {
+        for (r, text) in reader.lines().enumerate() {
+            let text = text?;
+            let line = common::Line::new(r + 1, text.trim());
+            if r > 0 && line.text.chars().count() != ncols {
+                let message = format!("expected {ncols} columns");
+                return Err(line.error(line.text, message).into());
+            }
             nrows = r + 1;
-            for (col, c) in line?.trim().chars().enumerate() {
-                ncols = col + 1;
+            ncols = 0;
+            for (i, c) in line.text.char_indices() {
+                ncols += 1;
                 let tile = match c {
                     '.' => Tile::Space,
                     '/' => Tile::FwdMirror,
                     '\\' => Tile::BwdMirror,
                     '|' => Tile::VBeam,
                     '-' => Tile::HBeam,
-                    _ => anyhow::bail!("invalid char: {}", c),
+                    _ => {
+                        let at = &line.text[i..i + c.len_utf8()];
+                        return Err(line.error(at, "unknown tile").into());
+                    }
                 };
                 vals.push(tile);
             }
         }
+        if nrows == 0 || ncols == 0 {
+            return Err(common::Error::Invalid("empty map".into()));
+        }
+        // Node coordinates are stored as u8, and one past the last row or column must fit too.
+        if nrows > 255 || ncols > 255 {
+            return Err(common::Error::Invalid("map is larger than 255x255".into()));
+        }
         // println!("nrows: {}, ncols: {}", nrows, ncols);
         // println!("len: {}", vals.len());
-        let tiles = ndarray::Array2::from_shape_vec((nrows, ncols), vals)?;
+        let tiles = ndarray::Array2::from_shape_vec((nrows, ncols), vals).unwrap();
         Ok(Self { tiles })
     }
 
@@ -136,7 +153,7 @@ impl Map {
     }
 }
 
-pub fn part1<R: std::io::BufRead>(reader: R) -> anyhow::Result<usize> {
+pub fn part1<R: std::io::BufRead>(reader: R) -> common::Result<usize> {
     let map = Map::read(reader)?;
     let seen = map.visit(std::collections::HashSet::new(), NodeId::new(0, 0, Dir::W));
     Ok(seen
@@ -146,7 +163,7 @@ pub fn part1<R: std::io::BufRead>(reader: R) -> anyhow::Result<usize> {
         .len())
 }
 
-pub fn part2<R: std::io::BufRead>(reader: R) -> anyhow::Result<usize> {
+pub fn part2<R: std::io::BufRead>(reader: R) -> common::Result<usize> {
     let map = Map::read(reader)?;
     let result = std::iter::empty()
         .chain((0..map.tiles.ncols()).map(|c| NodeId::new(0, c, Dir::N)))
@@ -161,10 +178,27 @@ pub fn part2<R: std::io::BufRead>(reader: R) -> anyhow::Result<usize> {
                 .len()
         })
         .max()
-        .ok_or(anyhow::anyhow!("huh"))?;
+        .ok_or_else(|| common::Error::Invalid("empty map".into()))?;
     Ok(result)
 }
 
+pub struct Day16;
+
+impl common::Solver for Day16 {
+    type Params1 = ();
+    type Params2 = ();
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn part1(input: &str, _: &()) -> common::Result<usize> {
+        stacker::grow(32 * 1024 * 1024, || part1(input.as_bytes()))
+    }
+
+    fn part2(input: &str, _: &()) -> common::Result<usize> {
+        stacker::grow(32 * 1024 * 1024, || part2(input.as_bytes()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     const EXAMPLE1: &str = r".|...\....
@@ -180,7 +214,7 @@ mod tests {
 ";
 
     #[test]
-    fn part1_example() -> anyhow::Result<()> {
+    fn part1_example() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
         let result = super::part1(reader)?;
         assert_eq!(result, 46);
@@ -188,7 +222,24 @@ mod tests {
     }
 
     #[test]
-    fn part1_final() -> anyhow::Result<()> {
+    fn map_size() -> common::Result<()> {
+        let map = |size| format!("{}\n", ".".repeat(size)).repeat(size);
+        assert_eq!(super::part1(map(255).as_bytes())?, 255);
+        assert!(matches!(
+            super::part1(map(256).as_bytes()),
+            Err(common::Error::Invalid(_))
+        ));
+        for empty in ["", "\n"] {
+            assert!(matches!(
+                super::part1(empty.as_bytes()),
+                Err(common::Error::Invalid(_))
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn part1_final() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
         let result = stacker::grow(32 * 1024 * 1024, || super::part1(reader))?;
         assert_eq!(result, 6740);
@@ -196,7 +247,7 @@ mod tests {
     }
 
     #[test]
-    fn part2_example() -> anyhow::Result<()> {
+    fn part2_example() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
         let result = super::part2(reader)?;
         assert_eq!(result, 51);
@@ -204,7 +255,7 @@ mod tests {
     }
 
     #[test]
-    fn part2_final() -> anyhow::Result<()> {
+    fn part2_final() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
         let result = stacker::grow(32 * 1024 * 1024, || super::part2(reader))?;
         assert_eq!(result, 7041);
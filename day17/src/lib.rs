@@ -167,18 +167,36 @@ struct Map {
 }
 
 impl Map {
-    fn read<R: std::io::BufRead>(reader: R) -> anyhow::Result<Self> {
+    fn read<R: std::io::BufRead>(reader: R) -> common::Result<Self> {
         let mut vals = vec![];
         let (mut nrows, mut ncols) = (0, 0);
-        for (r, line) in reader.lines().enumerate() {
-            for (c, val) in line?.trim().chars().enumerate() {
+        for (r, text) in reader.lines().enumerate() {
+            let text = text?;
+            let line = common::Line::new(r + 1, text.trim());
+            if r > 0 && line.text.chars().count() != ncols {
+                let message = format!("expected {ncols} columns");
+                return Err(line.error(line.text, message).into());
+            }
+            for (c, (i, val)) in line.text.char_indices().enumerate() {
                 nrows = r + 1;
                 ncols = c + 1;
-                vals.push(u8::try_from(val).unwrap() - b'0');
+                let digit = val.to_digit(10).ok_or_else(|| {
+                    line.error(&line.text[i..i + val.len_utf8()], "expected a digit")
+                })?;
+                vals.push(digit as u8);
             }
         }
+        if nrows == 0 {
+            return Err(common::Error::Invalid("empty map".into()));
+        }
+        if Slr::try_from(nrows.max(ncols)).is_err() {
+            return Err(common::Error::Invalid(format!(
+                "map is larger than {0}x{0}",
+                Slr::MAX
+            )));
+        }
         Ok(Map {
-            mtx: ndarray::Array2::from_shape_vec((nrows, ncols), vals)?,
+            mtx: ndarray::Array2::from_shape_vec((nrows, ncols), vals).unwrap(),
         })
     }
 
@@ -200,7 +218,7 @@ impl Map {
     }
 }
 
-pub fn part1<R: std::io::BufRead>(reader: R) -> anyhow::Result<usize> {
+pub fn part1<R: std::io::BufRead>(reader: R) -> common::Result<usize> {
     let map = Map::read(reader)?;
     let dists = map.solve::<NodeId>((0, 0));
     dists
@@ -211,10 +229,10 @@ pub fn part1<R: std::io::BufRead>(reader: R) -> anyhow::Result<usize> {
         })
         .map(|(_, &dist)| dist.try_into().unwrap())
         .min()
-        .ok_or(anyhow::anyhow!("no path found"))
+        .ok_or_else(|| common::Error::Invalid("no path found".into()))
 }
 
-pub fn part2<R: std::io::BufRead>(reader: R) -> anyhow::Result<usize> {
+pub fn part2<R: std::io::BufRead>(reader: R) -> common::Result<usize> {
     let map = Map::read(reader)?;
     let dists = map.solve::<NodeId2>((0, 0));
     dists
@@ -233,7 +251,24 @@ pub fn part2<R: std::io::BufRead>(reader: R) -> anyhow::Result<usize> {
         )
         .map(|(_, &dist)| dist.try_into().unwrap())
         .min()
-        .ok_or(anyhow::anyhow!("no path found"))
+        .ok_or_else(|| common::Error::Invalid("no path found".into()))
+}
+
+pub struct Day17;
+
+impl common::Solver for Day17 {
+    type Params1 = ();
+    type Params2 = ();
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn part1(input: &str, _: &()) -> common::Result<usize> {
+        part1(input.as_bytes())
+    }
+
+    fn part2(input: &str, _: &()) -> common::Result<usize> {
+        part2(input.as_bytes())
+    }
 }
 
 #[cfg(test)]
@@ -255,7 +290,7 @@ mod tests {
 ";
 
     #[test]
-    fn part1_example() -> anyhow::Result<()> {
+    fn part1_example() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
         let result = super::part1(reader)?;
         assert_eq!(result, 102);
@@ -263,7 +298,7 @@ mod tests {
     }
 
     #[test]
-    fn part1_big() -> anyhow::Result<()> {
+    fn part1_big() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
         let result = super::part1(reader)?;
         assert_eq!(result, 851);
@@ -279,7 +314,7 @@ mod tests {
 ";
 
     #[test]
-    fn part2_example() -> anyhow::Result<()> {
+    fn part2_example() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
         let result = super::part2(reader)?;
         assert_eq!(result, 94);
@@ -287,7 +322,7 @@ mod tests {
     }
 
     #[test]
-    fn part2_example2() -> anyhow::Result<()> {
+    fn part2_example2() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE2.as_bytes());
         let result = super::part2(reader)?;
         assert_eq!(result, 71);
@@ -295,7 +330,7 @@ mod tests {
     }
 
     #[test]
-    fn part2_big() -> anyhow::Result<()> {
+    fn part2_big() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
         let result = super::part2(reader)?;
         assert_eq!(result, 982);
@@ -22,7 +22,7 @@ impl Dir {
 }
 
 impl std::str::FromStr for Dir {
-    type Err = anyhow::Error;
+    type Err = common::ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
@@ -30,7 +30,7 @@ impl std::str::FromStr for Dir {
             "D" => Ok(Self::D),
             "L" => Ok(Self::L),
             "R" => Ok(Self::R),
-            _ => anyhow::bail!("invalid direction: {s}"),
+            _ => Err(common::Line::new(0, s).error(s, "unknown direction")),
         }
     }
 }
@@ -42,31 +42,36 @@ struct Inst {
 }
 
 impl std::str::FromStr for Inst {
-    type Err = anyhow::Error;
+    type Err = common::ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (dir, rest) = s.split_once(' ').ok_or(anyhow::anyhow!("no ' '"))?;
-        let dir = dir.parse()?;
-        let (cnt, _) = rest.split_once(' ').ok_or(anyhow::anyhow!("no ' '"))?;
-        let cnt = cnt.parse()?;
+        let line = common::Line::new(0, s);
+        let (dir, rest) = line.split_once(s, " ")?;
+        let dir = line.parse_inner(dir)?;
+        let (cnt, _) = line.split_once(rest, " ")?;
+        let cnt = line.parse(cnt)?;
         Ok(Self { dir, cnt })
     }
 }
 
 impl Inst {
-    fn from_color_str(s: &str) -> Self {
-        let (_, rest) = s.split_once('#').unwrap();
-        let (color, _) = rest.split_once(')').unwrap();
+    /// Reads the instruction hidden in the `(#rrrrrd)` color: five hex digits of count, then
+    /// one digit of direction.
+    fn from_color_str(line: &common::Line) -> Result<Self, common::ParseError> {
+        let (_, rest) = line.split_once(line.text, "#")?;
+        let (color, _) = line.split_once(rest, ")")?;
+        if color.len() != 6 || !color.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(line.error(color, "expected 6 hex digits"));
+        }
         let cnt = i64::from_str_radix(&color[..5], 16).unwrap();
-        let dir_ch = color.chars().nth(5).unwrap();
-        let dir = match dir_ch {
-            '0' => Dir::R,
-            '1' => Dir::D,
-            '2' => Dir::L,
-            '3' => Dir::U,
-            _ => panic!("invalid dir hex: {dir_ch}"),
+        let dir = match &color[5..] {
+            "0" => Dir::R,
+            "1" => Dir::D,
+            "2" => Dir::L,
+            "3" => Dir::U,
+            dir_ch => return Err(line.error(dir_ch, "unknown direction")),
         };
-        Self { dir, cnt }
+        Ok(Self { dir, cnt })
     }
 }
 
@@ -76,22 +81,23 @@ struct InstList {
 }
 
 impl std::str::FromStr for InstList {
-    type Err = anyhow::Error;
-    fn from_str(s: &str) -> anyhow::Result<Self> {
+    type Err = common::ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(Self {
-            insts: s
-                .lines()
-                .map(|l| l.parse::<Inst>())
+            insts: common::Line::all(s)
+                .map(|line| line.parse_inner::<Inst>(line.text))
                 .collect::<Result<_, _>>()?,
         })
     }
 }
 
 impl InstList {
-    fn from_color_str(s: &str) -> Self {
-        Self {
-            insts: s.lines().map(Inst::from_color_str).collect(),
-        }
+    fn from_color_str(s: &str) -> Result<Self, common::ParseError> {
+        Ok(Self {
+            insts: common::Line::all(s)
+                .map(|line| Inst::from_color_str(&line))
+                .collect::<Result<_, _>>()?,
+        })
     }
 }
 
@@ -228,22 +234,39 @@ impl std::fmt::Display for Map {
     }
 }
 
-pub fn part1<R: std::io::BufRead>(mut reader: R) -> u64 {
+pub fn part1<R: std::io::BufRead>(mut reader: R) -> common::Result<u64> {
     let mut input = String::new();
-    reader.read_to_string(&mut input).unwrap();
-    let inst_list: InstList = input.parse().unwrap();
+    reader.read_to_string(&mut input)?;
+    let inst_list: InstList = input.parse()?;
     let map = Map::from_inst_list(&inst_list);
     // println!("{map}");
-    map.inside_area()
+    Ok(map.inside_area())
 }
 
-pub fn part2<R: std::io::BufRead>(mut reader: R) -> u64 {
+pub fn part2<R: std::io::BufRead>(mut reader: R) -> common::Result<u64> {
     let mut input = String::new();
-    reader.read_to_string(&mut input).unwrap();
-    let inst_list = InstList::from_color_str(input.as_str());
+    reader.read_to_string(&mut input)?;
+    let inst_list = InstList::from_color_str(input.as_str())?;
     let map = Map::from_inst_list(&inst_list);
     // println!("{map}");
-    map.inside_area()
+    Ok(map.inside_area())
+}
+
+pub struct Day18;
+
+impl common::Solver for Day18 {
+    type Params1 = ();
+    type Params2 = ();
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn part1(input: &str, _: &()) -> common::Result<u64> {
+        stacker::grow(32 * 1024 * 1024, || part1(input.as_bytes()))
+    }
+
+    fn part2(input: &str, _: &()) -> common::Result<u64> {
+        stacker::grow(256 * 1024 * 1024, || part2(input.as_bytes()))
+    }
 }
 
 #[cfg(test)]
@@ -266,30 +289,43 @@ U 2 (#7a21e3)
 ";
 
     #[test]
-    fn part1_example() {
+    fn part1_example() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
-        let result = super::part1(reader);
+        let result = super::part1(reader)?;
         assert_eq!(result, 62);
+        Ok(())
     }
 
     #[test]
-    fn part1_big() {
+    fn part1_big() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
-        let result = stacker::grow(32 * 1024 * 1024, || super::part1(reader));
+        let result = stacker::grow(32 * 1024 * 1024, || super::part1(reader))?;
         assert_eq!(result, 48795);
+        Ok(())
     }
 
     #[test]
-    fn part2_example() {
+    fn part2_example() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
-        let result = super::part2(reader);
+        let result = super::part2(reader)?;
         assert_eq!(result, 952408144115);
+        Ok(())
     }
 
     #[test]
-    fn part2_big() {
+    fn part2_big() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
-        let result = stacker::grow(256 * 1024 * 1024, || super::part2(reader));
+        let result = stacker::grow(256 * 1024 * 1024, || super::part2(reader))?;
         assert_eq!(result, 40654918441248);
+        Ok(())
+    }
+
+    #[test]
+    fn part2_malformed() {
+        let reader = std::io::BufReader::new("R 6 (#70c710)\nD 5 (#0dc574)\n".as_bytes());
+        let Err(common::Error::Parse(err)) = super::part2(reader) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 12, "4"));
     }
 }
@@ -1,5 +1,3 @@
-use anyhow::{anyhow, bail};
-
 use std::cmp::Ordering;
 
 #[derive(Debug, Clone)]
@@ -46,11 +44,12 @@ impl PartRange {
 }
 
 impl std::str::FromStr for Part {
-    type Err = anyhow::Error;
+    type Err = common::ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (_, rest) = s.split_once('{').ok_or(anyhow!("no '{{' in {s:?}"))?;
-        let (vals, _) = rest.split_once('}').ok_or(anyhow!("no '}}' in {s:?}"))?;
+        let line = common::Line::new(0, s);
+        let (_, rest) = line.split_once(s, "{")?;
+        let (vals, _) = line.split_once(rest, "}")?;
         let mut part = Self {
             x: i16::MIN,
             m: i16::MIN,
@@ -58,9 +57,9 @@ impl std::str::FromStr for Part {
             s: i16::MIN,
         };
         for val in vals.split(',') {
-            let (attr, val) = val.split_once('=').ok_or(anyhow!("no '-' in {val:?}"))?;
-            let attr: Attr = attr.parse()?;
-            let val: i16 = val.parse()?;
+            let (attr, val) = line.split_once(val, "=")?;
+            let attr: Attr = line.parse_inner(attr)?;
+            let val: i16 = line.parse(val)?;
             attr.set(&mut part, val);
         }
         Ok(part)
@@ -95,7 +94,7 @@ impl Attr {
 }
 
 impl std::str::FromStr for Attr {
-    type Err = anyhow::Error;
+    type Err = common::ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
@@ -103,7 +102,7 @@ impl std::str::FromStr for Attr {
             "m" => Ok(Self::M),
             "a" => Ok(Self::A),
             "s" => Ok(Self::S),
-            _ => bail!("invalid attr: {s}"),
+            _ => Err(common::Line::new(0, s).error(s, "unknown attribute")),
         }
     }
 }
@@ -112,11 +111,12 @@ impl std::str::FromStr for Attr {
 struct WfId([u8; 3]);
 
 impl std::str::FromStr for WfId {
-    type Err = anyhow::Error;
+    type Err = common::ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() > 3 {
-            bail!("too long: {s}");
+        if s.is_empty() || s.len() > 3 {
+            let message = "expected a workflow name of 1 to 3 bytes";
+            return Err(common::Line::new(0, s).error(s, message));
         }
         let mut bytes = [0; 3];
         bytes[..s.len()].copy_from_slice(s.as_bytes());
@@ -192,22 +192,23 @@ impl Cond {
 }
 
 impl std::str::FromStr for Cond {
-    type Err = anyhow::Error;
+    type Err = common::ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = common::Line::new(0, s);
+        // Only < and > appear in workflows, and eval_range handles no others.
         let (attr, rhs) = s
-            .split_once(['<', '>', '='])
-            .ok_or(anyhow!("no op found in {s}"))?;
-        let cmp = match s.chars().nth(attr.len()).ok_or(anyhow!("unreachable"))? {
-            '<' => Ordering::Less,
-            '>' => Ordering::Greater,
-            '=' => Ordering::Equal,
-            _ => bail!("invalid op: {s}"),
+            .split_once(['<', '>'])
+            .ok_or_else(|| line.error(s, "expected '<' or '>'"))?;
+        let cmp = if s[attr.len()..].starts_with('<') {
+            Ordering::Less
+        } else {
+            Ordering::Greater
         };
         Ok(Self {
-            attr: attr.parse()?,
+            attr: line.parse_inner(attr)?,
             cmp,
-            rhs: rhs.parse()?,
+            rhs: line.parse(rhs)?,
         })
     }
 }
@@ -220,7 +221,7 @@ enum Act {
 }
 
 impl std::str::FromStr for Act {
-    type Err = anyhow::Error;
+    type Err = common::ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
@@ -263,12 +264,13 @@ impl Rule {
 }
 
 impl std::str::FromStr for Rule {
-    type Err = anyhow::Error;
+    type Err = common::ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = common::Line::new(0, s);
         match s.split_once(':') {
             None => Ok(Self::Act(s.parse()?)),
-            Some((cond, act)) => Ok(Self::Cond(cond.parse()?, act.parse()?)),
+            Some((cond, act)) => Ok(Self::Cond(line.parse_inner(cond)?, line.parse_inner(act)?)),
         }
     }
 }
@@ -298,16 +300,21 @@ impl Workflow {
 }
 
 impl std::str::FromStr for Workflow {
-    type Err = anyhow::Error;
+    type Err = common::ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (id, rest) = s.split_once('{').ok_or(anyhow!("no {{ in {s}"))?;
-        let id = id.parse()?;
-        let (rules, _) = rest.split_once('}').ok_or(anyhow!("no }} in {s}"))?;
-        let rules = rules
+        let line = common::Line::new(0, s);
+        let (id, rest) = line.split_once(s, "{")?;
+        let id = line.parse_inner(id)?;
+        let (rules_str, _) = line.split_once(rest, "}")?;
+        let rules: Vec<Rule> = rules_str
             .split(',')
-            .map(|r| r.parse())
+            .map(|r| line.parse_inner(r))
             .collect::<Result<_, _>>()?;
+        if let Some(Rule::Cond(..)) = rules.last() {
+            let last = rules_str.rsplit(',').next().unwrap();
+            return Err(line.error(last, "last rule must not have a condition"));
+        }
         Ok(Self { id, rules })
     }
 }
@@ -350,16 +357,21 @@ impl WorkflowSet {
     }
 }
 
-impl std::str::FromStr for WorkflowSet {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut workflows = std::collections::HashMap::new();
-        for line in s.lines() {
-            let workflow: Workflow = line.trim().parse()?;
-            workflows.insert(workflow.id, workflow);
+impl WorkflowSet {
+    /// Checks that `in` and every workflow a rule sends to exist.
+    fn validate(&self) -> common::Result<()> {
+        let targets = self.workflows.values().flat_map(|wf| {
+            wf.rules.iter().filter_map(|rule| match rule {
+                Rule::Act(Act::Send(id)) | Rule::Cond(_, Act::Send(id)) => Some(*id),
+                _ => None,
+            })
+        });
+        for id in std::iter::once("in".parse().unwrap()).chain(targets) {
+            if !self.workflows.contains_key(&id) {
+                return Err(common::Error::Invalid(format!("no workflow named {id}")));
+            }
         }
-        Ok(Self { workflows })
+        Ok(())
     }
 }
 
@@ -368,43 +380,65 @@ struct PartSet {
     parts: Vec<Part>,
 }
 
-impl std::str::FromStr for PartSet {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = Vec::new();
-        for line in s.lines() {
-            let part: Part = line.trim().parse()?;
-            parts.push(part);
+/// Reads the workflows and, after a blank line, the parts.
+fn parse_input(input: &str) -> common::Result<(WorkflowSet, PartSet)> {
+    let mut workflows = std::collections::HashMap::new();
+    let mut parts = Vec::new();
+    let mut lines = common::Line::all(input);
+    for line in lines.by_ref() {
+        if line.text.trim().is_empty() {
+            break;
         }
-        Ok(Self { parts })
+        let workflow: Workflow = line.parse_inner(line.text.trim())?;
+        workflows.insert(workflow.id, workflow);
     }
+    for line in lines {
+        let part: Part = line.parse_inner(line.text.trim())?;
+        parts.push(part);
+    }
+    let workflow_set = WorkflowSet { workflows };
+    workflow_set.validate()?;
+    Ok((workflow_set, PartSet { parts }))
 }
 
-pub fn part1<R: std::io::BufRead>(mut reader: R) -> i64 {
+pub fn part1<R: std::io::BufRead>(mut reader: R) -> common::Result<i64> {
     let mut input = String::new();
-    reader.read_to_string(&mut input).unwrap();
-    let (workflow_set, part_set) = input.split_once("\n\n").unwrap();
-    let workflow_set: WorkflowSet = workflow_set.parse().unwrap();
-    let part_set: PartSet = part_set.parse().unwrap();
-    part_set
+    reader.read_to_string(&mut input)?;
+    let (workflow_set, part_set) = parse_input(&input)?;
+    Ok(part_set
         .parts
         .iter()
         .filter(|p| workflow_set.process(p) == Act::Accept)
         .map(|&Part { x, m, a, s }| [x, m, a, s].iter().map(|&v| i64::from(v)).sum::<i64>())
-        .sum()
+        .sum())
 }
 
-pub fn part2<R: std::io::BufRead>(mut reader: R) -> u64 {
+pub fn part2<R: std::io::BufRead>(mut reader: R) -> common::Result<u64> {
     let mut input = String::new();
-    reader.read_to_string(&mut input).unwrap();
-    let (workflow_set, _) = input.split_once("\n\n").unwrap();
-    let workflow_set: WorkflowSet = workflow_set.parse().unwrap();
-    workflow_set
+    reader.read_to_string(&mut input)?;
+    let (workflow_set, _) = parse_input(&input)?;
+    Ok(workflow_set
         .accepted_ranges()
         .iter()
         .map(|p| p.card())
-        .sum()
+        .sum())
+}
+
+pub struct Day19;
+
+impl common::Solver for Day19 {
+    type Params1 = ();
+    type Params2 = ();
+    type Answer1 = i64;
+    type Answer2 = u64;
+
+    fn part1(input: &str, _: &()) -> common::Result<i64> {
+        part1(input.as_bytes())
+    }
+
+    fn part2(input: &str, _: &()) -> common::Result<u64> {
+        part2(input.as_bytes())
+    }
 }
 
 #[cfg(test)]
@@ -430,30 +464,44 @@ hdj{m>838:A,pv}
 ";
 
     #[test]
-    fn part1_example() {
+    fn part1_example() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
-        let result = super::part1(reader);
+        let result = super::part1(reader)?;
         assert_eq!(result, 19114);
+        Ok(())
     }
 
     #[test]
-    fn part1_big() {
+    fn part1_big() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
-        let result = super::part1(reader);
+        let result = super::part1(reader)?;
         assert_eq!(result, 432788);
+        Ok(())
     }
 
     #[test]
-    fn part2_example() {
+    fn part2_example() -> common::Result<()> {
         let reader = std::io::BufReader::new(EXAMPLE1.as_bytes());
-        let result = super::part2(reader);
+        let result = super::part2(reader)?;
         assert_eq!(result, 167409079868000u64);
+        Ok(())
     }
 
     #[test]
-    fn part2_big() {
+    fn part2_big() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
-        let result = super::part2(reader);
+        let result = super::part2(reader)?;
         assert_eq!(result, 142863718918201u64);
+        Ok(())
+    }
+
+    #[test]
+    fn part1_malformed() {
+        let input = "in{x<10:A,y>5:R,A}\n\n{x=1,m=2,a=3,s=4}\n";
+        let reader = std::io::BufReader::new(input.as_bytes());
+        let Err(common::Error::Parse(err)) = super::part1(reader) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!((err.line, err.column, err.text.as_str()), (1, 11, "y"));
     }
 }
@@ -1,4 +1,3 @@
-use anyhow::anyhow;
 use smallvec::{smallvec, SmallVec};
 use std::fmt::Write;
 
@@ -30,7 +29,7 @@ impl ModId {
 }
 
 impl std::str::FromStr for ModId {
-    type Err = anyhow::Error;
+    type Err = common::ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if s == "broadcaster" {
@@ -39,8 +38,9 @@ impl std::str::FromStr for ModId {
             return Ok(Self::OUTPUT);
         }
 
-        if s.len() > MOD_ID_MAXLEN {
-            anyhow::bail!("more than {MOD_ID_MAXLEN} chars long: {s:?}");
+        if s.is_empty() || s.len() > MOD_ID_MAXLEN {
+            let message = format!("expected a module name of 1 to {MOD_ID_MAXLEN} bytes");
+            return Err(common::Line::new(0, s).error(s, message));
         }
         let mut id = [0u8; MOD_ID_MAXLEN];
         id[..s.len()].copy_from_slice(s.as_bytes());
@@ -164,37 +164,36 @@ impl Module {
 }
 
 impl std::str::FromStr for Module {
-    type Err = anyhow::Error;
+    type Err = common::ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (prefixed_name, dests) = s
-            .split_once("->")
-            .ok_or(anyhow!("' -> ' not found in {s:?}"))?;
+        let line = common::Line::new(0, s);
+        let (prefixed_name, dests) = line.split_once(s, "->")?;
         let prefixed_name = prefixed_name.trim();
         let dests: SmallVec<[ModId; 2]> = dests
             .split(',')
-            .map(|d| d.trim().parse())
+            .map(|d| line.parse_inner(d.trim()))
             .collect::<Result<_, _>>()?;
         if let Some(id) = prefixed_name.strip_prefix('%') {
             Ok(Self {
-                id: id.parse()?,
+                id: line.parse_inner(id)?,
                 dests,
                 behavior: ModuleBeh::FlipFlop(FlipFlopState::new()),
             })
         } else if let Some(id) = prefixed_name.strip_prefix('&') {
             Ok(Self {
-                id: id.parse()?,
+                id: line.parse_inner(id)?,
                 dests,
                 behavior: ModuleBeh::Conjunction(ConjunctionState::new()),
             })
         } else if prefixed_name == "broadcaster" || prefixed_name == "output" {
             Ok(Self {
-                id: prefixed_name.parse()?,
+                id: line.parse_inner(prefixed_name)?,
                 dests,
                 behavior: ModuleBeh::Broadcast(BroadcastState::new()),
             })
         } else {
-            anyhow::bail!("unknown module type: {prefixed_name:?}");
+            Err(line.error(prefixed_name, "unknown module type"))
         }
     }
 }
@@ -205,12 +204,11 @@ struct ModuleSet {
 }
 
 impl std::str::FromStr for ModuleSet {
-    type Err = anyhow::Error;
+    type Err = common::ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut modules: std::collections::BTreeMap<ModId, Module> = s
-            .lines()
-            .map(|l| l.parse::<Module>().map(|m| (m.id, m)))
+        let mut modules: std::collections::BTreeMap<ModId, Module> = common::Line::all(s)
+            .map(|line| line.parse_inner::<Module>(line.text).map(|m| (m.id, m)))
             .collect::<Result<_, _>>()?;
 
         let conns_to_add: Vec<(ModId, ModId)> = modules
@@ -238,10 +236,8 @@ impl std::str::FromStr for ModuleSet {
         modules.extend(terminal_modules_to_add.into_iter().map(|m| (m.id, m)));
 
         for (from_id, to_id) in conns_to_add {
-            let module = modules
-                .get_mut(&to_id)
-                .ok_or(anyhow!("unknown dest: {to_id}"))?;
-            module.add_input(from_id);
+            // Every destination has a module by now, if only a terminal one.
+            modules.get_mut(&to_id).unwrap().add_input(from_id);
         }
         Ok(Self { modules })
     }
@@ -266,10 +262,13 @@ impl ModuleSet {
     }
 }
 
-pub fn part1<R: std::io::BufRead>(mut reader: R, count: usize) -> u64 {
+pub fn part1<R: std::io::BufRead>(mut reader: R, count: usize) -> common::Result<u64> {
     let mut input = String::new();
-    reader.read_to_string(&mut input).unwrap();
-    let mut module_set = input.parse::<ModuleSet>().unwrap();
+    reader.read_to_string(&mut input)?;
+    let mut module_set = input.parse::<ModuleSet>()?;
+    if !module_set.modules.contains_key(&ModId::BROADCASTER) {
+        return Err(common::Error::Invalid("no broadcaster module".into()));
+    }
 
     let (mut hi_cnt, mut lo_cnt) = (0, 0);
     for _ in 0..count {
@@ -279,7 +278,7 @@ pub fn part1<R: std::io::BufRead>(mut reader: R, count: usize) -> u64 {
         });
     }
 
-    hi_cnt * lo_cnt
+    Ok(hi_cnt * lo_cnt)
 }
 
 #[allow(dead_code)]
@@ -334,6 +333,46 @@ fn part2_examine<R: std::io::BufRead>(mut reader: R) {
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct Presses {
+    pub count: usize,
+}
+
+impl Default for Presses {
+    fn default() -> Self {
+        Self { count: 1000 }
+    }
+}
+
+impl common::Params for Presses {
+    fn set(&mut self, name: &str, value: &str) -> common::Result<()> {
+        match name {
+            "count" => self.count = common::parse_param(name, value)?,
+            _ => return Err(common::Error::unknown_param(name)),
+        }
+        Ok(())
+    }
+}
+
+pub struct Day20;
+
+impl common::Solver for Day20 {
+    type Params1 = Presses;
+    type Params2 = ();
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn part1(input: &str, params: &Presses) -> common::Result<u64> {
+        part1(input.as_bytes(), params.count)
+    }
+
+    fn part2(_: &str, _: &()) -> common::Result<u64> {
+        Err(common::Error::Unsupported(
+            "part 2 was solved by inspecting the module graph, see part2_examine".into(),
+        ))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     const EXAMPLE1: &str = "\
@@ -352,7 +391,7 @@ broadcaster -> a
 ";
 
     #[test]
-    fn part1_pre() {
+    fn part1_pre() -> common::Result<()> {
         let test_data = [
             (EXAMPLE1, 1, 32),
             // (EXAMPLE1, 2, 64),
@@ -360,26 +399,29 @@ broadcaster -> a
         ];
         for (input, count, expected) in test_data {
             let reader = std::io::BufReader::new(input.as_bytes());
-            let result = super::part1(reader, count);
+            let result = super::part1(reader, count)?;
             assert_eq!(result, expected);
         }
+        Ok(())
     }
 
     #[test]
-    fn part1_example() {
+    fn part1_example() -> common::Result<()> {
         let test_data = [(EXAMPLE1, 32000000), (EXAMPLE2, 11687500)];
         for (input, expected) in test_data {
             let reader = std::io::BufReader::new(input.as_bytes());
-            let result = super::part1(reader, 1000);
+            let result = super::part1(reader, 1000)?;
             assert_eq!(result, expected);
         }
+        Ok(())
     }
 
     #[test]
-    fn part1_big() {
+    fn part1_big() -> common::Result<()> {
         let reader = std::io::BufReader::new(include_str!("big.txt").as_bytes());
-        let result = super::part1(reader, 1000);
+        let result = super::part1(reader, 1000)?;
         assert_eq!(result, 807069600);
+        Ok(())
     }
 
     #[test]
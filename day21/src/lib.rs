@@ -1,6 +1,15 @@
 #[derive(Debug, Clone)]
 struct PosVec(ndarray::Array1<u8>);
 
+/// How `AdjMtx::pow` multiplies matrices. Both give the same answers; sparse is faster on
+/// puzzle-sized maps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Compose {
+    #[default]
+    Sparse,
+    Dense,
+}
+
 #[derive(Debug, Clone)]
 struct AdjMtx(ndarray::Array2<u8>);
 
@@ -15,7 +24,6 @@ impl AdjMtx {
         PosVec(result)
     }
 
-    #[allow(dead_code)]
     fn compose_dense(&self, other: &Self) -> Self {
         let mut result = ndarray::Array2::<u8>::zeros(self.0.dim());
         for i in 0..self.0.nrows() {
@@ -28,7 +36,6 @@ impl AdjMtx {
         Self(result)
     }
 
-    #[allow(dead_code)]
     fn compose_sparse(&self, other: &Self) -> Self {
         let mut result = ndarray::Array2::<u8>::zeros(self.0.dim());
         let mut other_edges: Vec<(u16, u16)> = other
@@ -56,21 +63,24 @@ impl AdjMtx {
         Self(result)
     }
 
-    fn compose(&self, other: &Self) -> Self {
-        self.compose_sparse(other)
+    fn compose(&self, other: &Self, how: Compose) -> Self {
+        match how {
+            Compose::Sparse => self.compose_sparse(other),
+            Compose::Dense => self.compose_dense(other),
+        }
     }
 
-    fn pow(&self, n: usize) -> Self {
+    fn pow(&self, n: usize, how: Compose) -> Self {
         if n == 0 {
             Self(ndarray::Array2::<u8>::from_diag_elem(self.0.nrows(), 1))
         } else if n == 1 {
             self.clone()
         } else if n % 2 == 0 {
-            let half = self.pow(n / 2);
-            half.compose(&half)
+            let half = self.pow(n / 2, how);
+            half.compose(&half, how)
         } else {
-            let half = self.pow(n / 2);
-            half.compose(&half).compose(self)
+            let half = self.pow(n / 2, how);
+            half.compose(&half, how).compose(self, how)
         }
     }
 }
@@ -84,20 +94,26 @@ const DELTAS_I16: [(i16, i16); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
 const DELTAS_I32: [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
 
 impl Map {
-    fn from_str(s: &str) -> Self {
+    fn from_str(s: &str) -> common::Result<Self> {
         let mut map = ndarray::Array2::zeros((0, 0));
-        for line in s.lines() {
-            let line = line.trim();
-            let row = line
-                .chars()
-                .map(|c| u8::try_from(c).unwrap())
-                .collect::<ndarray::Array1<u8>>();
+        for line in common::Line::all(s) {
+            let line = common::Line::new(line.number, line.text.trim());
+            let unknown = line.text.char_indices().find(|&(_, c)| !".#S".contains(c));
+            if let Some((i, c)) = unknown {
+                let at = &line.text[i..i + c.len_utf8()];
+                return Err(line.error(at, "unknown tile").into());
+            }
+            let row = line.text.bytes().collect::<ndarray::Array1<u8>>();
+            if map.nrows() == 0 {
+                map = ndarray::Array2::zeros((0, row.len()));
+            }
             if row.len() != map.ncols() {
-                map = map.into_shape((0, row.len())).unwrap();
+                let message = format!("expected {} columns", map.ncols());
+                return Err(line.error(line.text, message).into());
             }
             map.push_row(row.view()).unwrap();
         }
-        Self { map }
+        Ok(Self { map })
     }
 
     fn coord_to_idx(&self, r: usize, c: usize) -> usize {
@@ -188,19 +204,22 @@ impl Map {
     }
 }
 
-pub fn part1(input: &str, steps: usize) -> usize {
-    let map = Map::from_str(input);
+pub fn part1(input: &str, steps: usize) -> common::Result<usize> {
+    part1_with(input, steps, Compose::default())
+}
+
+pub fn part1_with(input: &str, steps: usize, compose: Compose) -> common::Result<usize> {
+    let map = Map::from_str(input)?;
     let initial_vec = map.initial_pos_vec();
     let adj_mtx = map.adj_mtx();
-    println!("adj_mtx dim: {:?}", adj_mtx.0.dim());
-    let final_vec = adj_mtx.pow(steps).apply(&initial_vec);
-    final_vec.0.iter().filter(|&&v| v == 1).count()
+    let final_vec = adj_mtx.pow(steps, compose).apply(&initial_vec);
+    Ok(final_vec.0.iter().filter(|&&v| v == 1).count())
 }
 
-pub fn part2(input: &str, steps: usize) -> usize {
-    let map = Map::from_str(input);
+pub fn part2(input: &str, steps: usize) -> common::Result<usize> {
+    let map = Map::from_str(input)?;
     let final_set = map.exact_dist_set_wrapped(steps);
-    final_set.len()
+    Ok(final_set.len())
 }
 
 pub fn part2_solved(steps: usize) -> usize {
@@ -217,6 +236,75 @@ pub fn part2_solved(steps: usize) -> usize {
     (num / div).try_into().unwrap()
 }
 
+/// Number of steps to walk for part 1, 64 unless set, and the matrix product to walk them
+/// with.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Steps {
+    pub steps: Option<usize>,
+    pub compose: Compose,
+}
+
+impl common::Params for Steps {
+    fn set(&mut self, name: &str, value: &str) -> common::Result<()> {
+        match name {
+            "steps" => self.steps = Some(common::parse_param(name, value)?),
+            "compose" => {
+                self.compose = match value.trim() {
+                    "sparse" => Compose::Sparse,
+                    "dense" => Compose::Dense,
+                    _ => {
+                        return Err(common::Error::Invalid(format!(
+                            "parameter {name}={value}: expected sparse or dense"
+                        )))
+                    }
+                }
+            }
+            _ => return Err(common::Error::unknown_param(name)),
+        }
+        Ok(())
+    }
+}
+
+/// Number of steps to walk on the repeating map for part 2. There is no default because the
+/// simulation does not scale to the puzzle's step count (see `part2_solved`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WrappedSteps {
+    pub steps: Option<usize>,
+}
+
+impl common::Params for WrappedSteps {
+    fn set(&mut self, name: &str, value: &str) -> common::Result<()> {
+        match name {
+            "steps" => self.steps = Some(common::parse_param(name, value)?),
+            _ => return Err(common::Error::unknown_param(name)),
+        }
+        Ok(())
+    }
+}
+
+pub struct Day21;
+
+impl common::Solver for Day21 {
+    type Params1 = Steps;
+    type Params2 = WrappedSteps;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn part1(input: &str, params: &Steps) -> common::Result<usize> {
+        part1_with(input, params.steps.unwrap_or(64), params.compose)
+    }
+
+    fn part2(input: &str, params: &WrappedSteps) -> common::Result<usize> {
+        let steps = params.steps.ok_or_else(|| {
+            common::Error::Unsupported(
+                "part 2 was solved by fitting a quadratic, see part2_solved; or set steps=<n>"
+                    .into(),
+            )
+        })?;
+        part2(input, steps)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     const EXAMPLE1: &str = "\
@@ -234,35 +322,50 @@ mod tests {
 ";
 
     #[test]
-    fn part1_example() {
-        assert_eq!(super::part1(EXAMPLE1, 6), 16);
+    fn part1_example() -> common::Result<()> {
+        assert_eq!(super::part1(EXAMPLE1, 6)?, 16);
+        Ok(())
+    }
+
+    #[test]
+    fn part1_compose_dense() -> common::Result<()> {
+        for steps in [1, 6, 7] {
+            assert_eq!(
+                super::part1_with(EXAMPLE1, steps, super::Compose::Dense)?,
+                super::part1_with(EXAMPLE1, steps, super::Compose::Sparse)?
+            );
+        }
+        Ok(())
     }
 
     #[test]
-    fn part1_big() {
-        assert_eq!(super::part1(include_str!("big.txt"), 64), 3746);
+    fn part1_big() -> common::Result<()> {
+        assert_eq!(super::part1(include_str!("big.txt"), 64)?, 3746);
+        Ok(())
     }
 
     #[test]
-    fn part2_example() {
-        assert_eq!(super::part2(EXAMPLE1, 6), 16);
-        assert_eq!(super::part2(EXAMPLE1, 10), 50);
-        assert_eq!(super::part2(EXAMPLE1, 50), 1594);
-        assert_eq!(super::part2(EXAMPLE1, 100), 6536);
+    fn part2_example() -> common::Result<()> {
+        assert_eq!(super::part2(EXAMPLE1, 6)?, 16);
+        assert_eq!(super::part2(EXAMPLE1, 10)?, 50);
+        assert_eq!(super::part2(EXAMPLE1, 50)?, 1594);
+        assert_eq!(super::part2(EXAMPLE1, 100)?, 6536);
         // assert_eq!(super::part2(EXAMPLE1, 500), 167004);
         // assert_eq!(super::part2(EXAMPLE1, 1000), 668697);
         // assert_eq!(super::part2(EXAMPLE1, 5000), 16733044);
+        Ok(())
     }
 
     #[test]
-    fn part2_final_fragments() {
+    fn part2_final_fragments() -> common::Result<()> {
         for cnt in 0..=3 {
             let steps = cnt * 131 + 65;
-            let answer = super::part2(include_str!("big.txt"), steps);
+            let answer = super::part2(include_str!("big.txt"), steps)?;
             let steps2 = steps * steps;
             println!("a * {steps2} + b * {steps} + c = {answer}");
         }
         // assert_eq!(super::part2(include_str!("big.txt"), 65), 0);
+        Ok(())
     }
 
     #[test]
@@ -20,27 +20,27 @@ impl PartialOrd for Brick {
     }
 }
 impl Brick {
-    fn from_str(s: &str, id: u16) -> Self {
-        let (min, max) = s.trim().split_once('~').unwrap();
-        let (minx, rest) = min.split_once(',').unwrap();
-        let (miny, minz) = rest.split_once(',').unwrap();
-        let (maxx, rest) = max.split_once(',').unwrap();
-        let (maxy, maxz) = rest.split_once(',').unwrap();
-        let minx = minx.parse::<Scalar>().unwrap();
-        let miny = miny.parse::<Scalar>().unwrap();
-        let minz = minz.parse::<Scalar>().unwrap();
-        let maxx = maxx.parse::<Scalar>().unwrap();
-        let maxy = maxy.parse::<Scalar>().unwrap();
-        let maxz = maxz.parse::<Scalar>().unwrap();
+    fn from_line(line: &common::Line, id: u16) -> Result<Self, common::ParseError> {
+        let (min, max) = line.split_once(line.text.trim(), "~")?;
+        let (minx, rest) = line.split_once(min, ",")?;
+        let (miny, minz) = line.split_once(rest, ",")?;
+        let (maxx, rest) = line.split_once(max, ",")?;
+        let (maxy, maxz) = line.split_once(rest, ",")?;
+        let minx = line.parse::<Scalar>(minx)?;
+        let miny = line.parse::<Scalar>(miny)?;
+        let minz = line.parse::<Scalar>(minz)?;
+        let maxx = line.parse::<Scalar>(maxx)?;
+        let maxy = line.parse::<Scalar>(maxy)?;
+        let maxz = line.parse::<Scalar>(maxz)?;
         let (minx, maxx) = (minx.min(maxx), minx.max(maxx));
         let (miny, maxy) = (miny.min(maxy), miny.max(maxy));
         let (minz, maxz) = (minz.min(maxz), minz.max(maxz));
-        Self {
+        Ok(Self {
             id,
             x: (minx, maxx),
             y: (miny, maxy),
             z: (minz, maxz),
-        }
+        })
     }
 }
 
@@ -48,17 +48,17 @@ struct Tower {
     bricks: Vec<Brick>,
 }
 impl Tower {
-    fn from_str(s: &str) -> Self {
-        let mut current_id = 0;
-        let bricks = s
-            .lines()
-            .map(|line| {
-                let brick = Brick::from_str(line, current_id);
-                current_id += 1;
-                brick
-            })
-            .collect::<Vec<Brick>>();
-        Self { bricks }
+    fn from_str(s: &str) -> common::Result<Self> {
+        let mut bricks = vec![];
+        for (id, line) in common::Line::all(s).enumerate() {
+            let id =
+                u16::try_from(id).map_err(|_| common::Error::Invalid("too many bricks".into()))?;
+            bricks.push(Brick::from_line(&line, id)?);
+        }
+        if bricks.is_empty() {
+            return Err(common::Error::Invalid("no bricks".into()));
+        }
+        Ok(Self { bricks })
     }
 
     fn fall(&mut self) -> SupportChain {
@@ -141,26 +141,43 @@ impl SupportChain {
     }
 }
 
-pub fn part1(s: &str) -> usize {
-    let mut tower = Tower::from_str(s);
+pub fn part1(s: &str) -> common::Result<usize> {
+    let mut tower = Tower::from_str(s)?;
     let support_chain = tower.fall();
-    (0..support_chain.supports.nrows())
+    Ok((0..support_chain.supports.nrows())
         .filter(|&id| support_chain.chain(id.try_into().unwrap()) == 0)
-        .count()
+        .count())
 }
 
-pub fn part2_single(s: &str, id: u16) -> usize {
-    let mut tower = Tower::from_str(s);
+pub fn part2_single(s: &str, id: u16) -> common::Result<usize> {
+    let mut tower = Tower::from_str(s)?;
     let support_chain = tower.fall();
-    support_chain.chain(id)
+    Ok(support_chain.chain(id))
 }
 
-pub fn part2(s: &str) -> usize {
-    let mut tower = Tower::from_str(s);
+pub fn part2(s: &str) -> common::Result<usize> {
+    let mut tower = Tower::from_str(s)?;
     let support_chain = tower.fall();
-    (0..support_chain.supports.nrows())
+    Ok((0..support_chain.supports.nrows())
         .map(|id| support_chain.chain(id.try_into().unwrap()))
-        .sum()
+        .sum())
+}
+
+pub struct Day22;
+
+impl common::Solver for Day22 {
+    type Params1 = ();
+    type Params2 = ();
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn part1(input: &str, _: &()) -> common::Result<usize> {
+        part1(input)
+    }
+
+    fn part2(input: &str, _: &()) -> common::Result<usize> {
+        part2(input)
+    }
 }
 
 #[cfg(test)]
@@ -176,28 +193,33 @@ mod tests {
 ";
 
     #[test]
-    fn part1_example() {
-        assert_eq!(super::part1(EXAMPLE1), 5);
+    fn part1_example() -> common::Result<()> {
+        assert_eq!(super::part1(EXAMPLE1)?, 5);
+        Ok(())
     }
 
     #[test]
-    fn part1_big() {
-        assert_eq!(super::part1(include_str!("big.txt")), 395);
+    fn part1_big() -> common::Result<()> {
+        assert_eq!(super::part1(include_str!("big.txt"))?, 395);
+        Ok(())
     }
 
     #[test]
-    fn part2_single() {
-        assert_eq!(super::part2_single(EXAMPLE1, 0), 6);
-        assert_eq!(super::part2_single(EXAMPLE1, 5), 1);
+    fn part2_single() -> common::Result<()> {
+        assert_eq!(super::part2_single(EXAMPLE1, 0)?, 6);
+        assert_eq!(super::part2_single(EXAMPLE1, 5)?, 1);
+        Ok(())
     }
 
     #[test]
-    fn part2_example() {
-        assert_eq!(super::part2(EXAMPLE1), 7);
+    fn part2_example() -> common::Result<()> {
+        assert_eq!(super::part2(EXAMPLE1)?, 7);
+        Ok(())
     }
 
     #[test]
-    fn part2_final() {
-        assert_eq!(super::part2(include_str!("big.txt")), 64714);
+    fn part2_final() -> common::Result<()> {
+        assert_eq!(super::part2(include_str!("big.txt"))?, 64714);
+        Ok(())
     }
 }
@@ -35,13 +35,28 @@ impl SparseMap {
             .map(|(_, w)| *w)
     }
 
-    fn from_str(s: &str) -> Self {
-        let grid = s
-            .lines()
-            .map(|l| l.trim().as_bytes().to_vec())
-            .collect::<Vec<Vec<u8>>>();
+    fn from_str(s: &str) -> common::Result<Self> {
+        let mut grid: Vec<Vec<u8>> = vec![];
+        for line in common::Line::all(s) {
+            let line = common::Line::new(line.number, line.text.trim());
+            let unknown = line
+                .text
+                .char_indices()
+                .find(|&(_, c)| !".#<>^v".contains(c));
+            if let Some((i, c)) = unknown {
+                let at = &line.text[i..i + c.len_utf8()];
+                return Err(line.error(at, "unknown tile").into());
+            }
+            if let Some(first_row) = grid.first() {
+                if line.text.len() != first_row.len() {
+                    let message = format!("expected {} columns", first_row.len());
+                    return Err(line.error(line.text, message).into());
+                }
+            }
+            grid.push(line.text.as_bytes().to_vec());
+        }
         let nrows = grid.len();
-        let ncols = grid[0].len();
+        let ncols = grid.first().map_or(0, Vec::len);
         let mut map = Self {
             start: u32::MAX,
             end: u32::MAX,
@@ -84,9 +99,11 @@ impl SparseMap {
                 }
             }
         }
-        assert!(map.start != u32::MAX);
-        assert!(map.end != u32::MAX);
-        map
+        if map.start == u32::MAX || map.end == u32::MAX {
+            let message = "expected an open tile in the first and the last row";
+            return Err(common::Error::Invalid(message.into()));
+        }
+        Ok(map)
     }
 
     fn remove_vertex(&mut self, id_to_rm: u32) {
@@ -340,22 +357,39 @@ impl CompactMap {
     }
 }
 
-pub fn part1(s: &str) -> usize {
-    let mut map = SparseMap::from_str(s);
+pub fn part1(s: &str) -> common::Result<usize> {
+    let mut map = SparseMap::from_str(s)?;
     map.simplify();
     // println!("{}", map.to_graphviz());
-    map.longest_path().try_into().unwrap()
+    Ok(map.longest_path().try_into().unwrap())
 }
 
-pub fn part2(s: &str) -> usize {
-    let mut map = SparseMap::from_str(s);
+pub fn part2(s: &str) -> common::Result<usize> {
+    let mut map = SparseMap::from_str(s)?;
     map.simplify();
     // println!("{}", map.to_graphviz());
-    let mut map = SparseMap::from_str(s.replace(['<', '>', '^', 'v'], ".").as_str());
+    let mut map = SparseMap::from_str(s.replace(['<', '>', '^', 'v'], ".").as_str())?;
     map.simplify();
     let map = CompactMap::from_sparse_map(&map);
     // println!("{}", map.to_graphviz());
-    map.longest_path().try_into().unwrap()
+    Ok(map.longest_path().try_into().unwrap())
+}
+
+pub struct Day23;
+
+impl common::Solver for Day23 {
+    type Params1 = ();
+    type Params2 = ();
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn part1(input: &str, _: &()) -> common::Result<usize> {
+        part1(input)
+    }
+
+    fn part2(input: &str, _: &()) -> common::Result<usize> {
+        part2(input)
+    }
 }
 
 #[cfg(test)]
@@ -387,23 +421,27 @@ mod tests {
 ";
 
     #[test]
-    fn part1_example() {
-        assert_eq!(super::part1(EXAMPLE1), 94);
+    fn part1_example() -> common::Result<()> {
+        assert_eq!(super::part1(EXAMPLE1)?, 94);
+        Ok(())
     }
 
     #[test]
-    fn part1_big() {
-        assert_eq!(super::part1(include_str!("big.txt")), 2070);
+    fn part1_big() -> common::Result<()> {
+        assert_eq!(super::part1(include_str!("big.txt"))?, 2070);
+        Ok(())
     }
 
     #[test]
-    fn part2_example() {
-        assert_eq!(super::part2(EXAMPLE1), 154);
+    fn part2_example() -> common::Result<()> {
+        assert_eq!(super::part2(EXAMPLE1)?, 154);
+        Ok(())
     }
 
     #[test]
-    fn part2_big() {
+    fn part2_big() -> common::Result<()> {
         // not 6715
-        assert_eq!(super::part2(include_str!("big.txt")), 6498);
+        assert_eq!(super::part2(include_str!("big.txt"))?, 6498);
+        Ok(())
     }
 }
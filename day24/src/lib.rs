@@ -200,6 +200,13 @@ pub struct AAB2 {
 }
 
 impl AAB2 {
+    pub fn new((min_x, min_y): (f64, f64), (max_x, max_y): (f64, f64)) -> Self {
+        Self {
+            min: Pt2(min_x, min_y),
+            max: Pt2(max_x, max_y),
+        }
+    }
+
     fn intersects_ir(self, ir: RayIntersectResult) -> bool {
         match ir {
             RayIntersectResult::None => false,
@@ -243,14 +250,10 @@ impl AAB2 {
 struct Pt3(f64, f64, f64);
 
 impl Pt3 {
-    fn from_str(s: &str) -> Pt3 {
-        let (x, rest) = s.split_once(',').unwrap();
-        let (y, z) = rest.split_once(',').unwrap();
-        Pt3(
-            x.trim().parse().unwrap(),
-            y.trim().parse().unwrap(),
-            z.trim().parse().unwrap(),
-        )
+    fn parse(line: &common::Line, s: &str) -> Result<Pt3, common::ParseError> {
+        let (x, rest) = line.split_once(s, ",")?;
+        let (y, z) = line.split_once(rest, ",")?;
+        Ok(Pt3(line.parse(x)?, line.parse(y)?, line.parse(z)?))
     }
 
     fn discard_z(self) -> Pt2 {
@@ -266,12 +269,12 @@ struct Ray3 {
 }
 
 impl Ray3 {
-    fn from_str(s: &str) -> Ray3 {
-        let (pos, vel) = s.split_once('@').unwrap();
-        Ray3 {
-            pos: Pt3::from_str(pos.trim()),
-            vel: Pt3::from_str(vel.trim()),
-        }
+    fn from_line(line: &common::Line) -> Result<Ray3, common::ParseError> {
+        let (pos, vel) = line.split_once(line.text, "@")?;
+        Ok(Ray3 {
+            pos: Pt3::parse(line, pos)?,
+            vel: Pt3::parse(line, vel)?,
+        })
     }
 
     fn discard_z(self) -> Ray2 {
@@ -283,10 +286,15 @@ impl Ray3 {
     }
 }
 
-pub fn part1(s: &str, aab: AAB2) -> usize {
-    let rays = s
-        .lines()
-        .map(Ray3::from_str)
+fn parse_rays(s: &str) -> Result<Vec<Ray3>, common::ParseError> {
+    common::Line::all(s)
+        .map(|line| Ray3::from_line(&line))
+        .collect()
+}
+
+pub fn part1(s: &str, aab: AAB2) -> common::Result<usize> {
+    let rays = parse_rays(s)?
+        .into_iter()
         .map(Ray3::discard_z)
         .collect::<Vec<_>>();
     let mut count = 0;
@@ -299,20 +307,20 @@ pub fn part1(s: &str, aab: AAB2) -> usize {
             }
         }
     }
-    count
+    Ok(count)
 }
 
-pub fn part2(s: &str, subpath: &str) -> i64 {
+/// Writes a Sage script solving for the rock's position and velocity to `day24-py/{subpath}`.
+pub fn part2(s: &str, subpath: &str) -> common::Result<()> {
+    let rays = parse_rays(s)?;
+
     let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
-        .canonicalize()
-        .unwrap()
+        .canonicalize()?
         .parent()
         .unwrap()
         .join("day24-py")
         .join(subpath);
-    let mut file = std::fs::File::create(path).unwrap();
-
-    let rays = s.lines().map(Ray3::from_str).collect::<Vec<_>>();
+    let mut file = std::fs::File::create(path)?;
 
     writeln!(
         file,
@@ -324,8 +332,7 @@ def cross(a1, a2, a3, b1, b2, b3):
         a1 * b2 - a2 * b1,
     )
 "#
-    )
-    .unwrap();
+    )?;
 
     let all_vars = ["prx", "pry", "prz", "vrx", "vry", "vrz"]
         .into_iter()
@@ -334,25 +341,90 @@ def cross(a1, a2, a3, b1, b2, b3):
         .collect::<Vec<_>>()
         .join(",");
 
-    writeln!(file, "{all_vars} = var(\"{all_vars}\")").unwrap();
+    writeln!(file, "{all_vars} = var(\"{all_vars}\")")?;
 
-    writeln!(file, "eqs = []").unwrap();
+    writeln!(file, "eqs = []")?;
     for ray in rays.iter().cloned() {
         let Ray3 {
             pos: Pt3(pix, piy, piz),
             vel: Pt3(vix, viy, viz),
         } = ray;
 
-        writeln!(file, "(cx, cy, cz) = cross({pix} - prx, {piy} - pry, {piz} - prz, {vix} - vrx, {viy} - vry, {viz} - vrz)").unwrap();
-        writeln!(file, "eqs.append(cx == 0)").unwrap();
-        writeln!(file, "eqs.append(cy == 0)").unwrap();
-        writeln!(file, "eqs.append(cz == 0)").unwrap();
+        writeln!(file, "(cx, cy, cz) = cross({pix} - prx, {piy} - pry, {piz} - prz, {vix} - vrx, {viy} - vry, {viz} - vrz)")?;
+        writeln!(file, "eqs.append(cx == 0)")?;
+        writeln!(file, "eqs.append(cy == 0)")?;
+        writeln!(file, "eqs.append(cz == 0)")?;
     }
 
     // 12 eqs are enough for Sage to solve this
-    writeln!(file, "print(solve(eqs[:12], {all_vars}))").unwrap();
+    writeln!(file, "print(solve(eqs[:12], {all_vars}))")?;
 
-    0
+    Ok(())
+}
+
+/// Test area for part 1, applied to both axes.
+#[derive(Debug, Clone, Copy)]
+pub struct TestArea {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Default for TestArea {
+    fn default() -> Self {
+        Self {
+            min: 200000000000000.0,
+            max: 400000000000000.0,
+        }
+    }
+}
+
+impl common::Params for TestArea {
+    fn set(&mut self, name: &str, value: &str) -> common::Result<()> {
+        match name {
+            "min" => self.min = common::parse_param(name, value)?,
+            "max" => self.max = common::parse_param(name, value)?,
+            _ => return Err(common::Error::unknown_param(name)),
+        }
+        Ok(())
+    }
+}
+
+/// File under `day24-py` that part 2 writes its Sage script to.
+#[derive(Debug, Clone, Default)]
+pub struct SageScript {
+    pub subpath: Option<String>,
+}
+
+impl common::Params for SageScript {
+    fn set(&mut self, name: &str, value: &str) -> common::Result<()> {
+        match name {
+            "sage" => self.subpath = Some(value.into()),
+            _ => return Err(common::Error::unknown_param(name)),
+        }
+        Ok(())
+    }
+}
+
+pub struct Day24;
+
+impl common::Solver for Day24 {
+    type Params1 = TestArea;
+    type Params2 = SageScript;
+    type Answer1 = usize;
+    type Answer2 = String;
+
+    fn part1(input: &str, params: &TestArea) -> common::Result<usize> {
+        let TestArea { min, max } = *params;
+        part1(input, AAB2::new((min, min), (max, max)))
+    }
+
+    fn part2(input: &str, params: &SageScript) -> common::Result<String> {
+        let subpath = params.subpath.as_deref().ok_or_else(|| {
+            common::Error::Unsupported("part 2 emits a Sage script, set sage=<file>".into())
+        })?;
+        part2(input, subpath)?;
+        Ok(format!("wrote day24-py/{subpath}"))
+    }
 }
 
 #[cfg(test)]
@@ -366,7 +438,7 @@ mod tests {
 ";
 
     #[test]
-    fn part1_example() {
+    fn part1_example() -> common::Result<()> {
         assert_eq!(
             super::part1(
                 EXAMPLE1,
@@ -374,13 +446,14 @@ mod tests {
                     min: super::Pt2(7.0, 7.0),
                     max: super::Pt2(27.0, 27.0),
                 }
-            ),
+            )?,
             2
         );
+        Ok(())
     }
 
     #[test]
-    fn part1_big() {
+    fn part1_big() -> common::Result<()> {
         assert_eq!(
             super::part1(
                 include_str!("big.txt"),
@@ -388,19 +461,20 @@ mod tests {
                     min: super::Pt2(200000000000000.0, 200000000000000.0),
                     max: super::Pt2(400000000000000.0, 400000000000000.0),
                 }
-            ),
+            )?,
             15889
         );
+        Ok(())
     }
 
     #[test]
-    fn part2_example() {
-        super::part2(EXAMPLE1, "example.sage");
+    fn part2_example() -> common::Result<()> {
+        super::part2(EXAMPLE1, "example.sage")
     }
 
     #[test]
-    fn part2_big() {
+    fn part2_big() -> common::Result<()> {
         // Final answer 801386475216902
-        super::part2(include_str!("big.txt"), "big.sage");
+        super::part2(include_str!("big.txt"), "big.sage")
     }
 }